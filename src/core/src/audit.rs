@@ -0,0 +1,35 @@
+mod json_lines;
+mod stdout;
+
+pub use json_lines::JsonLinesAuditSink;
+pub use stdout::StdoutAuditSink;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::sync::Arc;
+
+// One executed command, built-in command or matched message
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    // rfc3339, UTC
+    pub timestamp: String,
+    pub invocation_id: Option<String>,
+    // slack user id
+    pub user: Option<String>,
+    // channel name, or id if the channel is unknown
+    pub channel: String,
+    pub thread_permalink: Option<String>,
+    pub handler: String,
+    pub args: Vec<String>,
+    // ok, user_error, upstream_error, error or stopped (by a middleware)
+    pub outcome: String,
+    pub duration_ms: u64,
+}
+
+// Receives a record of every handler invocation. Failures are logged and don't affect the command
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn record(&self, record: &AuditRecord) -> Result<()>;
+}
+pub type ArcAuditSink = Arc<dyn AuditSink>;
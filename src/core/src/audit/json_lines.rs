@@ -0,0 +1,42 @@
+use crate::audit::{AuditRecord, AuditSink};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+// Appends one json object per line to the file
+pub struct JsonLinesAuditSink {
+    file: Arc<Mutex<File>>,
+}
+
+impl JsonLinesAuditSink {
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Fail to open audit log {}", path.display()))?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+}
+
+#[async_trait]
+impl AuditSink for JsonLinesAuditSink {
+    async fn record(&self, record: &AuditRecord) -> Result<()> {
+        let line = serde_json::to_string(record)?;
+        // file writes are blocking, so they are kept off the runtime workers
+        let file = self.file.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut file = file.lock().map_err(|_| anyhow::anyhow!("audit log lock is poisoned"))?;
+            writeln!(file, "{line}")?;
+            file.flush()?;
+            Ok(())
+        })
+        .await?
+    }
+}
@@ -0,0 +1,14 @@
+use crate::audit::{AuditRecord, AuditSink};
+use anyhow::Result;
+use async_trait::async_trait;
+
+// Prints one json object per line to stdout, e.g. to be collected with container logs
+pub struct StdoutAuditSink;
+
+#[async_trait]
+impl AuditSink for StdoutAuditSink {
+    async fn record(&self, record: &AuditRecord) -> Result<()> {
+        println!("{}", serde_json::to_string(record)?);
+        Ok(())
+    }
+}
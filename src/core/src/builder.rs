@@ -0,0 +1,386 @@
+use crate::audit::{ArcAuditSink, JsonLinesAuditSink, StdoutAuditSink};
+use crate::channel_settings::CONFIG_COMMAND;
+use crate::config::{BotConfig, HandlerRegistry, StorageConfig};
+use crate::health::Health;
+use crate::listener::Listener;
+use crate::metrics::Metrics;
+use crate::middleware::ArcMiddleware;
+use crate::ops_server::{serve_ops, WorkspacesCell};
+use crate::reload::{reload_on_sighup, ConfigReloader, RELOAD_COMMAND};
+use crate::settings::{HelpMode, Logging, Settings};
+use crate::slack_cli::SlackCliImpl;
+use crate::state::BotState;
+use crate::storage::{ArcStorage, SledStorage};
+use crate::transport::Transport;
+use crate::workspaces::Workspaces;
+use crate::ArcMsgHandler;
+use anyhow::{bail, Result};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+// Configured bot, ready to serve
+pub struct SlackCmd {
+    oauth_token: String,
+    workspace_tokens: Vec<String>,
+    transport: Transport,
+    handlers: Vec<ArcMsgHandler>,
+    middlewares: Vec<ArcMiddleware>,
+    audit_sinks: Vec<ArcAuditSink>,
+    storage: Option<ArcStorage>,
+    settings: Settings,
+    reloader: Option<ConfigReloader>,
+}
+
+impl SlackCmd {
+    pub fn builder() -> SlackCmdBuilder {
+        SlackCmdBuilder::default()
+    }
+
+    pub async fn serve(self) -> Result<()> {
+        init_logging(&self.settings.logging)?;
+        let metrics = Arc::new(Metrics::new()?);
+        let health = Arc::new(Health::default());
+        let workspaces_cell = WorkspacesCell::default();
+        if let Some(bind_addr) = self.settings.metrics_addr {
+            let (metrics, health, workspaces) = (metrics.clone(), health.clone(), workspaces_cell.clone());
+            tokio::spawn(async move {
+                if let Err(err) = serve_ops(bind_addr, metrics, health, workspaces).await {
+                    log::error!("Ops server failed: {:#?}", err);
+                }
+            });
+        }
+        let slack_cli = Arc::new(SlackCliImpl::new(&self.oauth_token)?.with_metrics(metrics.clone()));
+        let mut state =
+            BotState::new(slack_cli, self.handlers, self.middlewares, self.settings, self.reloader, metrics, health)
+                .await?;
+        state.audit_sinks = self.audit_sinks;
+        if let Some(storage) = self.storage {
+            state.storage = storage;
+        }
+        let mut others = vec![];
+        for oauth_token in &self.workspace_tokens {
+            let slack_cli = Arc::new(SlackCliImpl::new(oauth_token)?.with_metrics(state.metrics.clone()));
+            others.push(Arc::new(state.for_workspace(slack_cli).await?));
+        }
+        let state = Arc::new(state);
+        reload_on_sighup(state.clone())?;
+        let workspaces = Arc::new(Workspaces::new(state, others));
+        let _ = workspaces_cell.set(workspaces.clone());
+        Listener::new(self.transport, workspaces).serve().await
+    }
+}
+
+#[derive(Default)]
+pub struct SlackCmdBuilder {
+    oauth_token: Option<String>,
+    workspace_tokens: Vec<String>,
+    transport: Option<Transport>,
+    handlers: Vec<ArcMsgHandler>,
+    middlewares: Vec<ArcMiddleware>,
+    audit_sinks: Vec<ArcAuditSink>,
+    storage: Option<ArcStorage>,
+    settings: Settings,
+    reloader: Option<ConfigReloader>,
+}
+
+impl SlackCmdBuilder {
+    // handlers are reloaded from the file on SIGHUP or `reload` command
+    pub fn from_config_file(path: impl AsRef<Path>, registry: Arc<HandlerRegistry>) -> Result<Self> {
+        let config = BotConfig::from_file(path.as_ref())?;
+        let mut builder = Self::from_config(&config, &registry)?;
+        builder.reloader = Some(ConfigReloader::new(path.as_ref().to_path_buf(), registry));
+        Ok(builder)
+    }
+
+    pub fn from_config(config: &BotConfig, registry: &HandlerRegistry) -> Result<Self> {
+        let mut builder = Self::default()
+            .oauth_token(&config.oauth_token)
+            .transport(config.transport())
+            .rerun_on_edit(config.rerun_on_edit);
+        for workspace in &config.workspaces {
+            builder = builder.workspace(&workspace.oauth_token);
+        }
+        if let Some(prefix) = &config.command_prefix {
+            builder = builder.command_prefix(prefix);
+        }
+        if let Some(help_mode) = config.help_mode {
+            builder = builder.help_mode(help_mode);
+        }
+        if let Some(error_message) = &config.error_message {
+            builder = builder.error_message(error_message);
+        }
+        match (&config.log_filter, config.log_json) {
+            (Some(filter), false) => builder = builder.logging(Logging::EnvFilter(filter.clone())),
+            (filter, true) => builder = builder.logging(Logging::Json(filter.clone().unwrap_or("info".into()))),
+            (None, false) => {}
+        }
+        if let Some(bind_addr) = config.metrics_addr {
+            builder = builder.metrics_addr(bind_addr);
+        }
+        if let Some(limit) = config.limits.max_concurrent_commands {
+            builder = builder.max_concurrent_commands(limit);
+        }
+        match &config.storage {
+            StorageConfig::Memory => {}
+            StorageConfig::Sled { path } => builder = builder.storage(Arc::new(SledStorage::open(path)?)),
+        }
+        if let Some(path) = &config.audit.file {
+            builder = builder.audit_sink(Arc::new(JsonLinesAuditSink::new(path)?));
+        }
+        if config.audit.stdout {
+            builder = builder.audit_sink(Arc::new(StdoutAuditSink));
+        }
+        for admin in &config.admins {
+            builder = builder.admin(admin);
+        }
+        for (command, builtin) in &config.builtins {
+            builder = builder.builtin_channels(command, &builtin.channels);
+        }
+        let (handlers, limits) = registry.create_all(&config.handlers)?;
+        builder = builder.handlers(handlers);
+        for (handler_name, limit) in limits {
+            builder = builder.handler_max_concurrent(&handler_name, limit);
+        }
+        Ok(builder)
+    }
+
+    pub fn oauth_token(mut self, oauth_token: &str) -> Self {
+        self.oauth_token = Some(oauth_token.into());
+        self
+    }
+
+    // bot token of one more workspace served by the same app, e.g. in Enterprise Grid
+    pub fn workspace(mut self, oauth_token: &str) -> Self {
+        self.workspace_tokens.push(oauth_token.into());
+        self
+    }
+
+    pub fn transport(mut self, transport: Transport) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    pub fn handler(mut self, handler: ArcMsgHandler) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+
+    pub fn handlers<I>(mut self, handlers: I) -> Self
+    where
+        I: IntoIterator<Item = ArcMsgHandler>,
+    {
+        self.handlers.extend(handlers);
+        self
+    }
+
+    pub fn middleware(mut self, middleware: ArcMiddleware) -> Self {
+        self.middlewares.push(middleware);
+        self
+    }
+
+    // receives a record of every handler invocation
+    pub fn audit_sink(mut self, sink: ArcAuditSink) -> Self {
+        self.audit_sinks.push(sink);
+        self
+    }
+
+    // storage for handlers data, InMemoryStorage by default
+    pub fn storage(mut self, storage: ArcStorage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    pub fn command_prefix(mut self, prefix: &str) -> Self {
+        self.settings.command_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn help_mode(mut self, help_mode: HelpMode) -> Self {
+        self.settings.help_mode = help_mode;
+        self
+    }
+
+    pub fn error_message(mut self, error_message: &str) -> Self {
+        self.settings.error_message = error_message.into();
+        self
+    }
+
+    pub fn max_concurrent_commands(mut self, limit: usize) -> Self {
+        self.settings.max_concurrent_commands = Some(limit);
+        self
+    }
+
+    // limit of `handler_name` commands handled at the same time, the rest are queued
+    pub fn handler_max_concurrent(mut self, handler_name: &str, limit: usize) -> Self {
+        self.settings.handler_limits.insert(handler_name.into(), limit);
+        self
+    }
+
+    pub fn rerun_on_edit(mut self, enabled: bool) -> Self {
+        self.settings.rerun_on_edit = enabled;
+        self
+    }
+
+    // slack user id allowed to use admin commands
+    pub fn admin(mut self, user_id: &str) -> Self {
+        self.settings.admins.insert(user_id.into());
+        self
+    }
+
+    // channel names the built-in command (`config` or `reload`) is available in, all channels by default
+    pub fn builtin_channels(mut self, command: &str, channels: &[String]) -> Self {
+        self.settings.builtin_channels.insert(command.into(), channels.iter().cloned().collect());
+        self
+    }
+
+    // serves prometheus metrics on http://{bind_addr}/metrics, and /healthz, /readyz probes
+    pub fn metrics_addr(mut self, bind_addr: SocketAddr) -> Self {
+        self.settings.metrics_addr = Some(bind_addr);
+        self
+    }
+
+    pub fn logging(mut self, logging: Logging) -> Self {
+        self.settings.logging = logging;
+        self
+    }
+
+    pub fn build(self) -> Result<SlackCmd> {
+        let oauth_token = match self.oauth_token {
+            Some(token) if !token.is_empty() => token,
+            _ => bail!("oauth_token is required"),
+        };
+        if self.workspace_tokens.iter().any(|token| token.is_empty() || *token == oauth_token) {
+            bail!("workspace oauth_token must be non-empty and differ from the primary one");
+        }
+        let transport = match self.transport {
+            Some(transport) => transport,
+            None => bail!("transport is required"),
+        };
+        if self.settings.command_prefix.as_ref().is_some_and(|prefix| prefix.trim().is_empty()) {
+            bail!("command_prefix must not be empty");
+        }
+        if self.settings.max_concurrent_commands == Some(0) {
+            bail!("max_concurrent_commands must be positive");
+        }
+        if let Some((handler_name, _)) = self.settings.handler_limits.iter().find(|(_, limit)| **limit == 0) {
+            bail!("max_concurrent of handler '{handler_name}' must be positive");
+        }
+        if let Some(command) =
+            self.settings.builtin_channels.keys().find(|name| !BUILTIN_COMMANDS.contains(&name.as_str()))
+        {
+            bail!("unknown built-in command '{command}', expected one of: {}", BUILTIN_COMMANDS.join(", "));
+        }
+
+        validate_handlers(&self.handlers)?;
+
+        Ok(SlackCmd {
+            oauth_token,
+            workspace_tokens: self.workspace_tokens,
+            transport,
+            handlers: self.handlers,
+            middlewares: self.middlewares,
+            audit_sinks: self.audit_sinks,
+            storage: self.storage,
+            settings: self.settings,
+            reloader: self.reloader,
+        })
+    }
+}
+
+// handlers can't be called by these names, so they are rejected
+const BUILTIN_COMMANDS: [&str; 2] = [CONFIG_COMMAND, RELOAD_COMMAND];
+
+pub(crate) fn validate_handlers(handlers: &[ArcMsgHandler]) -> Result<()> {
+    let mut registered = HashSet::new();
+    for handler in handlers {
+        if BUILTIN_COMMANDS.contains(&handler.name()) {
+            bail!("handler name '{}' is taken by a built-in command", handler.name());
+        }
+        for channel in handler.supported_channels() {
+            if !registered.insert((channel.as_str(), handler.name())) {
+                bail!("handler '{}' is registered twice for channel '{}'", handler.name(), channel);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn init_logging(logging: &Logging) -> Result<()> {
+    match logging {
+        Logging::Default if log::max_level() >= log::Level::Debug => {
+            let subscriber = tracing_subscriber::fmt().with_env_filter("slack_morphism=debug").finish();
+            tracing::subscriber::set_global_default(subscriber)?;
+        }
+        Logging::EnvFilter(filter) => {
+            let subscriber = tracing_subscriber::fmt().with_env_filter(filter.as_str()).finish();
+            tracing::subscriber::set_global_default(subscriber)?;
+            forward_log_records();
+        }
+        Logging::Json(filter) => {
+            let subscriber = tracing_subscriber::fmt().json().with_env_filter(filter.as_str()).finish();
+            tracing::subscriber::set_global_default(subscriber)?;
+            forward_log_records();
+        }
+        Logging::Default | Logging::Disabled => {}
+    }
+    Ok(())
+}
+
+// handlers log with `log` crate, forward it to tracing to get invocation span fields
+fn forward_log_records() {
+    if let Err(err) = tracing_log::LogTracer::init() {
+        log::warn!("log records are not forwarded to tracing: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handler::ALL_CHANNELS;
+    use crate::{MsgHandler, SlackMsgEv};
+    use async_trait::async_trait;
+
+    struct NamedHandler(&'static str);
+
+    #[async_trait]
+    impl MsgHandler for NamedHandler {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            ""
+        }
+
+        fn supported_channels(&self) -> &HashSet<String> {
+            &ALL_CHANNELS
+        }
+
+        async fn handle(&self, _args: &[String], _msg_ev: &SlackMsgEv, _state: &BotState) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn builder() -> SlackCmdBuilder {
+        SlackCmdBuilder::default().oauth_token("xoxb-test").transport(Transport::socket_mode("xapp-test"))
+    }
+
+    #[test]
+    fn rejects_handlers_named_after_builtins() {
+        for name in BUILTIN_COMMANDS {
+            let err = validate_handlers(&[Arc::new(NamedHandler(name))]).unwrap_err();
+            assert_eq!(err.to_string(), format!("handler name '{name}' is taken by a built-in command"));
+        }
+        assert!(validate_handlers(&[Arc::new(NamedHandler("jira"))]).is_ok());
+    }
+
+    #[test]
+    fn rejects_channels_of_unknown_builtins() {
+        let channels = vec!["general".to_string()];
+        assert!(builder().builtin_channels("reload", &channels).build().is_ok());
+        let err = builder().builtin_channels("help", &channels).build().err().unwrap();
+        assert_eq!(err.to_string(), "unknown built-in command 'help', expected one of: config, reload");
+    }
+}
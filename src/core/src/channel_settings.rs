@@ -0,0 +1,139 @@
+use crate::state::BotState;
+use crate::utils::extract_channel_thread;
+use crate::SlackMsgEv;
+use anyhow::Result;
+use slack_morphism::SlackChannelId;
+
+pub(crate) const CONFIG_COMMAND: &str = "config";
+pub(crate) const CONFIG_DESCRIPTION: &str =
+    "Channel settings: `config set <handler.key> <value> [--global]`, `config unset <handler.key> [--global]`, \
+    `config get <handler.key>`, `config list`";
+
+const GLOBAL_SCOPE: &str = "global";
+
+// storage namespace of the settings scope
+fn namespace(scope: &str) -> String {
+    format!("settings:{scope}")
+}
+
+impl BotState {
+    // channel setting with fallback to the global one, e.g. `jira.project`
+    pub async fn channel_setting(&self, channel: &SlackChannelId, key: &str) -> Result<Option<String>> {
+        if let Some(value) = self.storage.get(&namespace(channel.as_ref()), key).await? {
+            return Ok(Some(value));
+        }
+        self.storage.get(&namespace(GLOBAL_SCOPE), key).await
+    }
+
+    pub async fn typed_channel_setting<T>(&self, channel: &SlackChannelId, key: &str) -> Result<Option<T>>
+    where
+        T: std::str::FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        match self.channel_setting(channel, key).await? {
+            Some(value) => Ok(Some(value.parse()?)),
+            None => Ok(None),
+        }
+    }
+}
+
+// `config` command: channel settings are available to everyone in the channel, global ones to bot admins
+pub(crate) async fn handle_config_command(args: &[String], msg_ev: &SlackMsgEv, state: &BotState) -> Result<()> {
+    let (channel, thread) = extract_channel_thread(msg_ev)?;
+    let channel_ns = namespace(channel.as_ref());
+    let is_admin = msg_ev.sender.user.as_ref().is_some_and(|user| state.settings.admins.contains(&user.to_string()));
+
+    let args = args.iter().skip(1).map(String::as_str).collect::<Vec<_>>();
+    let reply = match args.as_slice() {
+        ["set", key, value] if is_valid_key(key) => {
+            state.storage.set(&channel_ns, key, value, None).await?;
+            format!("`{key}` is set to `{value}` in this channel")
+        }
+        ["set", key, value, "--global"] if is_valid_key(key) => match is_admin {
+            true => {
+                state.storage.set(&namespace(GLOBAL_SCOPE), key, value, None).await?;
+                format!("`{key}` is set to `{value}` globally")
+            }
+            false => "Only bot admins can change global settings".to_string(),
+        },
+        ["unset", key] => match state.storage.delete(&channel_ns, key).await? {
+            true => format!("`{key}` is unset in this channel"),
+            false => format!("`{key}` is not set in this channel"),
+        },
+        ["unset", key, "--global"] => match is_admin {
+            true => match state.storage.delete(&namespace(GLOBAL_SCOPE), key).await? {
+                true => format!("`{key}` is unset globally"),
+                false => format!("`{key}` is not set globally"),
+            },
+            false => "Only bot admins can change global settings".to_string(),
+        },
+        ["get", key] => match state.channel_setting(&channel, key).await? {
+            Some(value) => format!("`{key}` = `{value}`"),
+            None => format!("`{key}` is not set"),
+        },
+        ["list"] => {
+            let channel_settings = state.storage.list(&channel_ns, "").await?;
+            let global_settings = state.storage.list(&namespace(GLOBAL_SCOPE), "").await?;
+            format_settings(&channel_settings, &global_settings)
+        }
+        _ => format!("Usage: {CONFIG_DESCRIPTION}\nKeys look like `<handler>.<key>`, e.g. `jira.project`"),
+    };
+    state.slack_cli.send_reply(&channel, &thread, &reply).await
+}
+
+fn is_valid_key(key: &str) -> bool {
+    key.split_once('.').is_some_and(|(handler, name)| !handler.is_empty() && !name.is_empty())
+}
+
+fn format_settings(channel_settings: &[(String, String)], global_settings: &[(String, String)]) -> String {
+    if channel_settings.is_empty() && global_settings.is_empty() {
+        return "No settings".to_string();
+    }
+    let mut lines = vec![];
+    for (key, value) in channel_settings {
+        lines.push(format!("• `{key}` = `{value}`"));
+    }
+    for (key, value) in global_settings {
+        if !channel_settings.iter().any(|(channel_key, _)| channel_key == key) {
+            lines.push(format!("• `{key}` = `{value}` (global)"));
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn validates_keys() {
+        for key in ["jira.project", "jira.unfurl", "jira.project.extra"] {
+            assert!(is_valid_key(key), "{key}");
+        }
+        for key in ["jira", "", ".project", "jira.", "."] {
+            assert!(!is_valid_key(key), "{key}");
+        }
+    }
+
+    #[test]
+    fn formats_empty_settings() {
+        assert_eq!(format_settings(&[], &[]), "No settings");
+    }
+
+    #[test]
+    fn channel_settings_shadow_global_ones() {
+        let channel = settings(&[("jira.project", "OPS")]);
+        let global = settings(&[("jira.project", "DEV"), ("jira.unfurl", "false")]);
+        assert_eq!(format_settings(&channel, &global), "• `jira.project` = `OPS`\n• `jira.unfurl` = `false` (global)");
+    }
+
+    #[test]
+    fn formats_global_settings_only() {
+        let global = settings(&[("jira.project", "DEV")]);
+        assert_eq!(format_settings(&[], &global), "• `jira.project` = `DEV` (global)");
+    }
+}
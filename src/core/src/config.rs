@@ -0,0 +1,363 @@
+use crate::error::HandlerError;
+use crate::handler::ALL_CHANNELS_MARKER;
+use crate::interaction::interaction_user;
+use crate::settings::HelpMode;
+use crate::state::BotState;
+use crate::transport::Transport;
+use crate::webhook::WebhookRequest;
+use crate::workspaces::Workspaces;
+use crate::{ArcMsgHandler, MsgHandler, SlackMsgEv};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use slack_morphism::prelude::SlackInteractionEvent;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// Bot assembly described in a TOML or YAML file.
+// `${VAR}` and `${VAR:-default}` in string values are replaced with environment variables.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BotConfig {
+    pub oauth_token: String,
+    // other workspaces served by the same app
+    #[serde(default)]
+    pub workspaces: Vec<WorkspaceConfig>,
+    pub transport: TransportConfig,
+    pub command_prefix: Option<String>,
+    pub help_mode: Option<HelpMode>,
+    pub error_message: Option<String>,
+    #[serde(default)]
+    pub rerun_on_edit: bool,
+    // env filter for the tracing subscriber
+    pub log_filter: Option<String>,
+    #[serde(default)]
+    pub log_json: bool,
+    // address of /metrics, /healthz and /readyz, e.g. "0.0.0.0:9090"
+    pub metrics_addr: Option<SocketAddr>,
+    // slack user ids allowed to use admin commands, e.g. `reload`
+    #[serde(default)]
+    pub admins: Vec<String>,
+    // built-in command name (`config` or `reload`) -> its settings
+    #[serde(default)]
+    pub builtins: HashMap<String, BuiltinConfig>,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub handlers: Vec<HandlerConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    pub oauth_token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case", deny_unknown_fields)]
+pub enum TransportConfig {
+    SocketMode {
+        socket_token: String,
+    },
+    Http {
+        bind_addr: SocketAddr,
+        signing_secret: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LimitsConfig {
+    pub max_concurrent_commands: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case", deny_unknown_fields)]
+pub enum StorageConfig {
+    #[default]
+    Memory,
+    // directory of sled database
+    Sled {
+        path: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuditConfig {
+    // json-lines file, appended
+    pub file: Option<PathBuf>,
+    #[serde(default)]
+    pub stdout: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuiltinConfig {
+    #[serde(default = "all_channels")]
+    pub channels: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HandlerConfig {
+    // factory name in HandlerRegistry
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default = "all_channels")]
+    pub channels: Vec<String>,
+    #[serde(default)]
+    pub permissions: PermissionsConfig,
+    #[serde(default)]
+    pub limits: HandlerLimitsConfig,
+    // handler specific parameters, see the handler factory
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PermissionsConfig {
+    // slack user ids allowed to call the handler, everyone if empty
+    #[serde(default)]
+    pub allowed_users: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HandlerLimitsConfig {
+    pub max_concurrent: Option<usize>,
+}
+
+fn all_channels() -> Vec<String> {
+    vec![ALL_CHANNELS_MARKER.to_string()]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+}
+
+impl BotConfig {
+    // format is detected by file extension
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let format = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            _ => bail!("Unsupported config format: {}", path.display()),
+        };
+        let raw = std::fs::read_to_string(path).with_context(|| format!("Fail to read config {}", path.display()))?;
+        Self::parse(&raw, format).with_context(|| format!("Fail to load config {}", path.display()))
+    }
+
+    pub fn parse(raw: &str, format: ConfigFormat) -> Result<Self> {
+        let mut value: serde_json::Value = match format {
+            ConfigFormat::Toml => toml::from_str(raw)?,
+            ConfigFormat::Yaml => serde_yaml_ng::from_str(raw)?,
+        };
+        interpolate_values(&mut value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    pub fn transport(&self) -> Transport {
+        match &self.transport {
+            TransportConfig::SocketMode { socket_token } => Transport::socket_mode(socket_token),
+            TransportConfig::Http {
+                bind_addr,
+                signing_secret,
+            } => Transport::http(*bind_addr, signing_secret),
+        }
+    }
+}
+
+impl HandlerConfig {
+    pub fn params<T: DeserializeOwned>(&self) -> Result<T> {
+        serde_json::from_value(self.params.clone())
+            .with_context(|| format!("Invalid params for handler type '{}'", self.kind))
+    }
+}
+
+pub type HandlerFactory = Box<dyn Fn(&HandlerConfig) -> Result<ArcMsgHandler> + Send + Sync>;
+
+// Handler type name -> factory creating handler instances from config
+#[derive(Default)]
+pub struct HandlerRegistry {
+    factories: HashMap<String, HandlerFactory>,
+}
+
+impl HandlerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<F>(&mut self, kind: &str, factory: F)
+    where
+        F: Fn(&HandlerConfig) -> Result<ArcMsgHandler> + Send + Sync + 'static,
+    {
+        if self.factories.insert(kind.to_string(), Box::new(factory)).is_some() {
+            log::warn!("handler factory '{kind}' was overridden");
+        }
+    }
+
+    // creates handler, restricted with config permissions
+    pub fn create(&self, config: &HandlerConfig) -> Result<ArcMsgHandler> {
+        let factory =
+            self.factories.get(&config.kind).ok_or_else(|| anyhow!("Unknown handler type '{}'", config.kind))?;
+        let handler = factory(config)?;
+        if config.permissions.allowed_users.is_empty() {
+            return Ok(handler);
+        }
+        Ok(Arc::new(RestrictedHandler {
+            inner: handler,
+            allowed_users: config.permissions.allowed_users.iter().cloned().collect(),
+        }))
+    }
+
+    // creates handlers and collects their concurrency limits: handler_name -> max_concurrent
+    pub(crate) fn create_all(&self, configs: &[HandlerConfig]) -> Result<(Vec<ArcMsgHandler>, HashMap<String, usize>)> {
+        let mut handlers = vec![];
+        let mut limits = HashMap::new();
+        for config in configs {
+            let handler = self.create(config)?;
+            match config.limits.max_concurrent {
+                Some(0) => bail!("max_concurrent of handler '{}' must be positive", handler.name()),
+                Some(limit) => {
+                    limits.insert(handler.name().to_string(), limit);
+                }
+                None => {}
+            }
+            handlers.push(handler);
+        }
+        Ok((handlers, limits))
+    }
+}
+
+struct RestrictedHandler {
+    inner: ArcMsgHandler,
+    allowed_users: HashSet<String>,
+}
+
+#[async_trait]
+impl MsgHandler for RestrictedHandler {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn supported_channels(&self) -> &HashSet<String> {
+        self.inner.supported_channels()
+    }
+
+    async fn handle(&self, args: &[String], msg_ev: &SlackMsgEv, state: &BotState) -> Result<()> {
+        let user = msg_ev.sender.user.as_ref().map(|user| user.to_string()).unwrap_or_default();
+        if !self.allowed_users.is_empty() && !self.allowed_users.contains(&user) {
+            return Err(HandlerError::unauthorized(format!("You are not allowed to use `{}`", self.name())).into());
+        }
+        self.inner.handle(args, msg_ev, state).await
+    }
+
+    fn message_pattern(&self) -> Option<&Regex> {
+        self.inner.message_pattern()
+    }
+
+    // plain messages are not commands, so they are not restricted
+    async fn handle_message(&self, matches: &[String], msg_ev: &SlackMsgEv, state: &BotState) -> Result<()> {
+        self.inner.handle_message(matches, msg_ev, state).await
+    }
+
+    // there is no thread to reply to, so interactions of other users are only logged
+    async fn handle_interaction(&self, event: &SlackInteractionEvent, state: &BotState) -> Result<()> {
+        let user = interaction_user(event).map(|user| user.to_string()).unwrap_or_default();
+        if !self.allowed_users.is_empty() && !self.allowed_users.contains(&user) {
+            log::info!("interaction of user '{user}' with '{}' was ignored: not allowed", self.name());
+            return Ok(());
+        }
+        self.inner.handle_interaction(event, state).await
+    }
+
+    async fn handle_webhook(&self, request: &WebhookRequest, workspaces: &Workspaces) -> Result<()> {
+        self.inner.handle_webhook(request, workspaces).await
+    }
+}
+
+fn interpolate_values(value: &mut serde_json::Value) -> Result<()> {
+    match value {
+        serde_json::Value::String(text) => *text = interpolate_env(text)?,
+        serde_json::Value::Array(items) => items.iter_mut().try_for_each(interpolate_values)?,
+        serde_json::Value::Object(fields) => fields.values_mut().try_for_each(interpolate_values)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn interpolate_env(raw: &str) -> Result<String> {
+    let mut result = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => bail!("Unclosed '${{' in config"),
+        };
+        let expr = &rest[start + 2..end];
+        let (name, default) = match expr.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expr, None),
+        };
+        let value = match (std::env::var(name), default) {
+            (Ok(value), _) => value,
+            (Err(_), Some(default)) => default.to_string(),
+            (Err(err), None) => bail!("Fail to interpolate env var '{name}': {err}"),
+        };
+        result.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolates_env_vars() {
+        std::env::set_var("SLACK_CMD_TEST_TOKEN", "xoxb-1");
+        let raw = "token = \"${SLACK_CMD_TEST_TOKEN}\"\nname = \"bot\"";
+        assert_eq!(interpolate_env(raw).unwrap(), "token = \"xoxb-1\"\nname = \"bot\"");
+    }
+
+    #[test]
+    fn uses_default_of_unset_var() {
+        assert_eq!(interpolate_env("a=${SLACK_CMD_TEST_UNSET:-x}").unwrap(), "a=x");
+        assert_eq!(interpolate_env("a=${SLACK_CMD_TEST_UNSET:-}").unwrap(), "a=");
+    }
+
+    #[test]
+    fn fails_on_unset_var_without_default() {
+        assert!(interpolate_env("${SLACK_CMD_TEST_UNSET}").is_err());
+    }
+
+    #[test]
+    fn fails_on_unclosed_expression() {
+        assert!(interpolate_env("a=${SLACK_CMD_TEST_TOKEN").is_err());
+    }
+
+    #[test]
+    fn keeps_text_without_expressions() {
+        assert_eq!(interpolate_env("a = \"$b {c}\"").unwrap(), "a = \"$b {c}\"");
+    }
+}
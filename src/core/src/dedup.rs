@@ -0,0 +1,140 @@
+use slack_morphism::events::SlackEventId;
+use slack_morphism::{SlackChannelId, SlackTs};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub(crate) const DEFAULT_DEDUP_WINDOW: Duration = Duration::from_secs(10 * 60);
+pub(crate) const DEFAULT_DEDUP_CAPACITY: usize = 10_000;
+
+// Slack redelivers events after socket reconnects and on retries,
+// so every event is checked against the recently seen event_id and (channel, ts) pairs.
+pub(crate) struct EventDedup {
+    window: Duration,
+    capacity: usize,
+    seen: Mutex<Seen>,
+    dropped: AtomicU64,
+}
+
+#[derive(Default)]
+struct Seen {
+    // key -> first time seen
+    first_seen: HashMap<String, Instant>,
+    // keys in the order they were seen, the oldest are evicted first
+    order: VecDeque<(String, Instant)>,
+}
+
+impl EventDedup {
+    pub fn new(window: Duration, capacity: usize) -> Self {
+        Self {
+            window,
+            capacity,
+            seen: Mutex::new(Seen::default()),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    // returns true if the event was already seen within the window
+    pub fn is_duplicate(
+        &self,
+        event_id: &SlackEventId,
+        channel: Option<&SlackChannelId>,
+        ts: Option<&SlackTs>,
+    ) -> bool {
+        let mut keys = vec![format!("event:{}", event_id)];
+        if let (Some(channel), Some(ts)) = (channel, ts) {
+            keys.push(format!("msg:{}:{}", channel, ts));
+        }
+        self.check(keys, Instant::now())
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn check(&self, keys: Vec<String>, now: Instant) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+        let seen = &mut *seen;
+        seen.evict(now, self.window, self.capacity);
+
+        let mut duplicate = false;
+        for key in keys {
+            match seen.first_seen.entry(key) {
+                Entry::Occupied(_) => duplicate = true,
+                Entry::Vacant(entry) => {
+                    seen.order.push_back((entry.key().clone(), now));
+                    entry.insert(now);
+                }
+            }
+        }
+        seen.evict(now, self.window, self.capacity);
+
+        if duplicate {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        duplicate
+    }
+}
+
+impl Seen {
+    // drops expired keys and the oldest ones over capacity
+    fn evict(&mut self, now: Instant, window: Duration, capacity: usize) {
+        while let Some((key, seen_at)) = self.order.front() {
+            if now.duration_since(*seen_at) < window && self.order.len() <= capacity {
+                break;
+            }
+            self.first_seen.remove(key);
+            self.order.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|key| key.to_string()).collect()
+    }
+
+    #[test]
+    fn detects_repeated_event_id() {
+        let dedup = EventDedup::new(Duration::from_secs(60), 10);
+        assert!(!dedup.is_duplicate(&"Ev1".into(), None, None));
+        assert!(dedup.is_duplicate(&"Ev1".into(), None, None));
+        assert!(!dedup.is_duplicate(&"Ev2".into(), None, None));
+        assert_eq!(dedup.dropped(), 1);
+    }
+
+    #[test]
+    fn detects_same_message_with_new_event_id() {
+        let dedup = EventDedup::new(Duration::from_secs(60), 10);
+        let (channel, ts) = ("C1".into(), "1.1".into());
+        assert!(!dedup.is_duplicate(&"Ev1".into(), Some(&channel), Some(&ts)));
+        assert!(dedup.is_duplicate(&"Ev2".into(), Some(&channel), Some(&ts)));
+    }
+
+    #[test]
+    fn window_starts_at_first_seen() {
+        let dedup = EventDedup::new(Duration::from_secs(60), 10);
+        let start = Instant::now();
+        assert!(!dedup.check(keys(&["a"]), start));
+        assert!(dedup.check(keys(&["a"]), start + Duration::from_secs(50)));
+        // repeats don't extend the window
+        assert!(!dedup.check(keys(&["a"]), start + Duration::from_secs(70)));
+    }
+
+    #[test]
+    fn evicts_oldest_over_capacity() {
+        let dedup = EventDedup::new(Duration::from_secs(60), 2);
+        let now = Instant::now();
+        for key in ["a", "b", "c"] {
+            assert!(!dedup.check(keys(&[key]), now));
+        }
+        assert!(dedup.check(keys(&["c"]), now));
+        assert!(!dedup.check(keys(&["a"]), now));
+        assert_eq!(dedup.seen.lock().unwrap().order.len(), 2);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::channel_settings::{CONFIG_COMMAND, CONFIG_DESCRIPTION};
 use crate::handler::ALL_CHANNELS_MARKER;
 use crate::utils::extract_channel_thread;
 use crate::{ArcMsgHandler, BotState, SlackMsgEv};
@@ -34,7 +35,7 @@ impl DefaultHelpHandler {
         }
     }
 
-    fn name(&self) -> &str {
+    pub(crate) fn name(&self) -> &str {
         "help"
     }
 
@@ -61,11 +62,16 @@ impl DefaultHelpHandler {
         } else {
             String::new()
         };
+        let config_info_msg = match state.is_builtin_available(&channel, CONFIG_COMMAND) {
+            true => format!("\n• `{CONFIG_COMMAND}`: {CONFIG_DESCRIPTION}"),
+            false => String::new(),
+        };
         let msg = format!(
-            "{}Available commands:\n• `{}`: {}{}",
+            "{}Available commands:\n• `{}`: {}{}{}",
             unknown_command_msg,
             self.name(),
             self.description(),
+            config_info_msg,
             all_info_msg
         );
         state.slack_cli.send_reply(&channel, &thread, &msg).await
@@ -0,0 +1,64 @@
+use std::fmt;
+
+// Error returned by handlers to control what is shown to the user.
+// Any other error is treated as Internal.
+#[derive(Debug)]
+pub enum HandlerError {
+    // user mistake, e.g. unknown project key; shown verbatim
+    User(String),
+    // request without valid credentials or from a user not allowed to run the command,
+    // e.g. unsigned webhook; shown verbatim
+    Unauthorized(String),
+    // failure of an external service; shown with a short reason
+    Upstream { service: String, reason: String },
+    // bug; shown as generic error message with a correlation id, which is also logged
+    Internal(anyhow::Error),
+}
+
+impl HandlerError {
+    pub fn user(msg: impl Into<String>) -> Self {
+        Self::User(msg.into())
+    }
+
+    pub fn unauthorized(msg: impl Into<String>) -> Self {
+        Self::Unauthorized(msg.into())
+    }
+
+    pub fn upstream(service: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Upstream {
+            service: service.into(),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn internal(err: impl Into<anyhow::Error>) -> Self {
+        Self::Internal(err.into())
+    }
+}
+
+impl fmt::Display for HandlerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandlerError::User(msg) | HandlerError::Unauthorized(msg) => write!(f, "{msg}"),
+            HandlerError::Upstream { service, reason } => write!(f, "{service} request failed: {reason}"),
+            HandlerError::Internal(err) => write!(f, "{err:#}"),
+        }
+    }
+}
+
+impl std::error::Error for HandlerError {}
+
+// audit outcome of commands stopped by a middleware
+pub(crate) const STOPPED_OUTCOME: &str = "stopped";
+
+// outcome label of handler result for metrics and audit
+pub(crate) fn outcome(result: &anyhow::Result<()>) -> &'static str {
+    match result {
+        Ok(_) => "ok",
+        Err(err) => match err.downcast_ref::<HandlerError>() {
+            Some(HandlerError::User(_) | HandlerError::Unauthorized(_)) => "user_error",
+            Some(HandlerError::Upstream { .. }) => "upstream_error",
+            _ => "error",
+        },
+    }
+}
@@ -1,7 +1,12 @@
+use crate::error::HandlerError;
 use crate::state::BotState;
+use crate::webhook::WebhookRequest;
+use crate::workspaces::Workspaces;
 use crate::SlackMsgEv;
 use anyhow::Result;
 use async_trait::async_trait;
+use regex::Regex;
+use slack_morphism::prelude::SlackInteractionEvent;
 use std::collections::HashSet;
 use std::sync::{Arc, LazyLock};
 
@@ -11,6 +16,26 @@ pub trait MsgHandler: Send + Sync {
     fn description(&self) -> &str;
     fn supported_channels(&self) -> &HashSet<String>;
     async fn handle(&self, args: &[String], msg_ev: &SlackMsgEv, state: &BotState) -> Result<()>;
+
+    // plain channel messages matching the pattern are passed to handle_message, e.g. to unfurl issue keys
+    fn message_pattern(&self) -> Option<&Regex> {
+        None
+    }
+
+    // matches: distinct matches of message_pattern in the message
+    async fn handle_message(&self, _matches: &[String], _msg_ev: &SlackMsgEv, _state: &BotState) -> Result<()> {
+        Ok(())
+    }
+
+    // block actions and view submissions with action_id or callback_id `{handler_name}:...`, e.g. `jira:create`
+    async fn handle_interaction(&self, _event: &SlackInteractionEvent, _state: &BotState) -> Result<()> {
+        Ok(())
+    }
+
+    // `POST /hooks/{handler_name}` of the ops server, workspaces are to reply to the right one
+    async fn handle_webhook(&self, _request: &WebhookRequest, _workspaces: &Workspaces) -> Result<()> {
+        Err(HandlerError::user(format!("Handler '{}' doesn't support webhooks", self.name())).into())
+    }
 }
 pub type ArcMsgHandler = Arc<dyn MsgHandler>;
 
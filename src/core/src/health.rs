@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Startup and connection state, reported by /healthz and /readyz.
+// Socket mode reconnects inside slack_morphism, which reports socket errors to the error handler
+// but not disconnects, so a socket closed by slack or without pongs stays connected until
+// the reconnect hello.
+#[derive(Default)]
+pub(crate) struct Health {
+    bot_info_loaded: AtomicBool,
+    channels_fetched: AtomicBool,
+    // socket mode: hello received and no socket error since; http mode: port is bound
+    connected: AtomicBool,
+}
+
+impl Health {
+    pub fn set_bot_info_loaded(&self) {
+        self.bot_info_loaded.store(true, Ordering::Relaxed);
+    }
+
+    pub fn set_channels_fetched(&self) {
+        self.channels_fetched.store(true, Ordering::Relaxed);
+    }
+
+    pub fn set_connected(&self, connected: bool) {
+        self.connected.store(connected, Ordering::Relaxed);
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.bot_info_loaded.load(Ordering::Relaxed)
+            && self.channels_fetched.load(Ordering::Relaxed)
+            && self.is_connected()
+    }
+
+    // names of the checks which are not passed yet
+    pub fn pending(&self) -> Vec<&'static str> {
+        let checks = [
+            ("bot_info", &self.bot_info_loaded),
+            ("channels", &self.channels_fetched),
+            ("connection", &self.connected),
+        ];
+        checks.into_iter().filter(|(_, passed)| !passed.load(Ordering::Relaxed)).map(|(name, _)| name).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_reconnects() {
+        let health = Health::default();
+        health.set_bot_info_loaded();
+        health.set_channels_fetched();
+        assert!(!health.is_ready());
+        assert_eq!(health.pending(), vec!["connection"]);
+
+        health.set_connected(true);
+        assert!(health.is_ready());
+        assert!(health.pending().is_empty());
+
+        health.set_connected(false);
+        assert!(!health.is_ready());
+        assert_eq!(health.pending(), vec!["connection"]);
+
+        health.set_connected(true);
+        assert!(health.is_ready());
+    }
+
+    #[test]
+    fn connection_alone_is_not_ready() {
+        let health = Health::default();
+        health.set_connected(true);
+        assert!(!health.is_ready());
+        assert_eq!(health.pending(), vec!["bot_info", "channels"]);
+    }
+}
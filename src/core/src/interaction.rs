@@ -0,0 +1,32 @@
+use slack_morphism::prelude::{SlackInteractionEvent, SlackView};
+use slack_morphism::{SlackTeamId, SlackUserId};
+
+// Interactive components of handlers have action_id (buttons, selects) or callback_id (modals)
+// like `{handler_name}:{id}`, so block actions and view submissions are routed to the handler.
+pub(crate) fn interaction_handler_name(event: &SlackInteractionEvent) -> Option<&str> {
+    let id = match event {
+        SlackInteractionEvent::BlockActions(event) => event.actions.as_ref()?.first()?.action_id.as_ref(),
+        SlackInteractionEvent::ViewSubmission(event) => match &event.view.view {
+            SlackView::Modal(view) => view.callback_id.as_ref()?.as_ref(),
+            SlackView::Home(view) => view.callback_id.as_ref()?.as_ref(),
+        },
+        _ => return None,
+    };
+    id.split_once(':').map(|(handler_name, _)| handler_name)
+}
+
+pub(crate) fn interaction_team(event: &SlackInteractionEvent) -> Option<&SlackTeamId> {
+    match event {
+        SlackInteractionEvent::BlockActions(event) => Some(&event.team.id),
+        SlackInteractionEvent::ViewSubmission(event) => Some(&event.team.id),
+        _ => None,
+    }
+}
+
+pub(crate) fn interaction_user(event: &SlackInteractionEvent) -> Option<&SlackUserId> {
+    match event {
+        SlackInteractionEvent::BlockActions(event) => event.user.as_ref().map(|user| &user.id),
+        SlackInteractionEvent::ViewSubmission(event) => Some(&event.user.id),
+        _ => None,
+    }
+}
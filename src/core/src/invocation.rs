@@ -0,0 +1,24 @@
+use slack_morphism::{SlackChannelId, SlackUserId};
+use tracing::Span;
+
+tokio::task_local! {
+    static INVOCATION_ID: String;
+}
+
+// id of the command being handled, e.g. to pass it to external services as request id
+pub fn current_invocation_id() -> Option<String> {
+    INVOCATION_ID.try_with(|id| id.clone()).ok()
+}
+
+pub(crate) fn new_invocation_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..12].to_string()
+}
+
+pub(crate) fn invocation_span(invocation_id: &str, channel: &SlackChannelId, user: Option<&SlackUserId>) -> Span {
+    let user = user.map(|user| user.to_string()).unwrap_or_default();
+    tracing::info_span!("command", %invocation_id, %channel, %user, handler = tracing::field::Empty)
+}
+
+pub(crate) async fn with_invocation_id<F: std::future::Future>(invocation_id: String, fut: F) -> F::Output {
+    INVOCATION_ID.scope(invocation_id, fut).await
+}
@@ -1,21 +1,60 @@
+mod audit;
+mod builder;
+mod channel_settings;
+mod config;
+mod dedup;
 mod default_help_handler;
+mod error;
 mod handler;
+mod health;
+mod interaction;
+mod invocation;
 mod listener;
+mod metrics;
+mod middleware;
+mod ops_server;
+mod queue;
+mod reload;
+mod replies;
+mod settings;
 mod slack_cli;
 mod slack_msg;
 mod state;
+mod storage;
+#[cfg(test)]
+mod test_utils;
+mod transport;
+mod webhook;
+mod workspaces;
 
 pub mod utils;
+pub use crate::audit::{ArcAuditSink, AuditRecord, AuditSink, JsonLinesAuditSink, StdoutAuditSink};
+pub use crate::builder::{SlackCmd, SlackCmdBuilder};
+pub use crate::config::{
+    AuditConfig, BotConfig, ConfigFormat, HandlerConfig, HandlerFactory, HandlerLimitsConfig, HandlerRegistry,
+    LimitsConfig, PermissionsConfig, StorageConfig, TransportConfig, WorkspaceConfig,
+};
+pub use crate::error::HandlerError;
 pub use crate::handler::{ArcMsgHandler, MsgHandler, ALL_CHANNELS};
+pub use crate::invocation::current_invocation_id;
+pub use crate::middleware::{ArcMiddleware, Middleware};
+pub use crate::settings::{HelpMode, Logging};
 pub use crate::slack_msg::{SlackMsg, SlackMsgEv, SlackMsgHist};
 pub use crate::state::BotState;
+pub use crate::storage::{ArcStorage, InMemoryStorage, SledStorage, Storage};
+pub use crate::transport::Transport;
+pub use crate::webhook::WebhookRequest;
+pub use crate::workspaces::Workspaces;
 
 pub async fn run<I>(oauth_token: &str, socket_token: &str, msg_handlers: I) -> anyhow::Result<()>
 where
     I: IntoIterator<Item = ArcMsgHandler>,
 {
-    let slack_cli = std::sync::Arc::new(slack_cli::SlackCliImpl::new(oauth_token)?);
-    let state = BotState::new(slack_cli, msg_handlers).await?;
-    let listener = listener::Listener::new(socket_token.into(), state);
-    listener.serve().await
+    SlackCmd::builder()
+        .oauth_token(oauth_token)
+        .transport(Transport::socket_mode(socket_token))
+        .handlers(msg_handlers)
+        .build()?
+        .serve()
+        .await
 }
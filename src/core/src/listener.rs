@@ -1,178 +1,565 @@
-use crate::state::BotState;
-use anyhow::Result;
+mod http_mode;
 
-use crate::utils::{extract_channel_thread, extract_msg_body};
+use crate::audit::AuditRecord;
+use crate::channel_settings::{handle_config_command, CONFIG_COMMAND};
+use crate::error::{outcome, HandlerError, STOPPED_OUTCOME};
+use crate::interaction::{interaction_handler_name, interaction_team, interaction_user};
+use crate::invocation::{current_invocation_id, invocation_span, new_invocation_id, with_invocation_id};
+use crate::reload::{handle_reload_command, RELOAD_COMMAND};
+use crate::settings::HelpMode;
+use crate::state::{BotState, Handlers};
+use crate::transport::Transport;
+use crate::workspaces::Workspaces;
+use anyhow::{bail, Result};
+
+use crate::utils::{extract_channel_id, extract_channel_thread, extract_msg_body, extract_msg_ts, extract_thread_ts};
 use crate::{ArcMsgHandler, SlackMsgEv};
+use slack_morphism::errors::SlackClientError;
+use slack_morphism::events::{SlackCommandEventResponse, SlackInteractionResponse, SlackMessageEventType};
 use slack_morphism::prelude::{
     HttpStatusCode, SlackClientEventsListenerEnvironment, SlackClientEventsUserState, SlackClientHyperConnector,
-    SlackEventCallbackBody, SlackHyperClient, SlackPushEventCallback,
+    SlackCommandEvent, SlackEventCallbackBody, SlackHyperClient, SlackHyperListenerEnvironment, SlackInteractionEvent,
+    SlackPushEventCallback,
 };
 use slack_morphism::{
     SlackApiToken, SlackChannelId, SlackClient, SlackClientSocketModeConfig, SlackClientSocketModeListener,
-    SlackSocketModeListenerCallbacks, SlackTs,
+    SlackMessageContent, SlackMessageOrigin, SlackMessageResponseType, SlackMessageSender,
+    SlackSocketModeListenerCallbacks, SlackTeamId, SlackTs,
 };
+use std::future::Future;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::SemaphorePermit;
+use tracing::{Instrument, Span};
 
 pub(crate) struct Listener {
-    socket_token: String,
-    state: Arc<BotState>,
+    transport: Transport,
+    workspaces: Arc<Workspaces>,
 }
 
 impl Listener {
-    pub fn new(socket_token: String, state: BotState) -> Self {
-        Self {
-            socket_token,
-            state: Arc::new(state),
-        }
+    pub fn new(transport: Transport, workspaces: Arc<Workspaces>) -> Self {
+        Self { transport, workspaces }
     }
 
     pub async fn serve(&self) -> Result<()> {
-        if log::max_level() >= log::Level::Debug {
-            let subscriber = tracing_subscriber::fmt().with_env_filter("slack_morphism=debug").finish();
-            tracing::subscriber::set_global_default(subscriber)?;
-        }
-
-        let socket_mode_callbacks = SlackSocketModeListenerCallbacks::new()
-            // .with_command_events(commands_dispatcher)
-            // .with_interaction_events(interactions_dispatcher)
-            .with_push_events(push_events_dispatcher);
-
         let slack_cli = Arc::new(SlackClient::new(SlackClientHyperConnector::new()?));
         let listener_env = Arc::new(
             SlackClientEventsListenerEnvironment::new(slack_cli)
                 .with_error_handler(error_handler)
-                .with_user_state(self.state.clone()),
+                .with_user_state(self.workspaces.clone()),
         );
 
-        let listener = SlackClientSocketModeListener::new(
-            &SlackClientSocketModeConfig::new(),
-            listener_env.clone(),
-            socket_mode_callbacks,
-        );
+        let result = match &self.transport {
+            Transport::SocketMode { socket_token } => serve_socket_mode(listener_env, socket_token).await,
+            Transport::Http {
+                bind_addr,
+                signing_secret,
+            } => http_mode::serve_http(listener_env, *bind_addr, signing_secret, &self.workspaces).await,
+        };
+        self.workspaces.all().for_each(|workspace| workspace.health.set_connected(false));
+        result
+    }
+}
+
+async fn serve_socket_mode(listener_env: Arc<SlackHyperListenerEnvironment>, socket_token: &str) -> Result<()> {
+    let socket_mode_callbacks = SlackSocketModeListenerCallbacks::new()
+        .with_hello_events(hello_dispatcher)
+        .with_command_events(commands_dispatcher)
+        .with_interaction_events(interactions_dispatcher)
+        .with_push_events(push_events_dispatcher);
+
+    let listener =
+        SlackClientSocketModeListener::new(&SlackClientSocketModeConfig::new(), listener_env, socket_mode_callbacks);
 
-        listener.listen_for(&SlackApiToken::new(self.socket_token.clone().into())).await?;
+    listener.listen_for(&SlackApiToken::new(socket_token.into())).await?;
 
-        let exit_code = listener.serve().await;
-        if exit_code != 0 {
-            log::error!("Listener exited with non-zero code={}", exit_code);
+    let exit_code = listener.serve().await;
+    if exit_code != 0 {
+        bail!("Listener exited with non-zero code={}", exit_code);
+    }
+    Ok(())
+}
+
+// state of the workspace the event came from
+async fn get_bot_state(state: &SlackClientEventsUserState, team_id: Option<&SlackTeamId>) -> Option<Arc<BotState>> {
+    let context_lock = state.read().await;
+    let workspaces = match context_lock.get_user_state::<Arc<Workspaces>>() {
+        Some(workspaces) => workspaces,
+        None => {
+            log::error!("Bot state is missing");
+            return None;
         }
-        Ok(())
+    };
+    let bot_state = workspaces.get(team_id);
+    if bot_state.is_none() {
+        log::warn!("event of unknown workspace team_id='{}' was dropped", team_id.map(|id| id.as_ref()).unwrap_or("-"));
     }
+    bot_state
+}
+
+// the socket is shared by all workspaces.
+// Sync, since it's also called from the sync error handler
+fn set_connected(state: &SlackClientEventsUserState, connected: bool) {
+    let context_lock = match state.try_read() {
+        Ok(context_lock) => context_lock,
+        Err(_) => return log::warn!("Bot state is locked, connection state is not updated"),
+    };
+    match context_lock.get_user_state::<Arc<Workspaces>>() {
+        Some(workspaces) => workspaces.all().for_each(|workspace| workspace.health.set_connected(connected)),
+        None => log::error!("Bot state is missing"),
+    }
+}
+
+// sent by slack on every (re)connect of socket mode.
+// Generic, since slack_morphism doesn't export the hello event type
+async fn hello_dispatcher<E: std::fmt::Debug>(
+    event: E,
+    _client: Arc<SlackHyperClient>,
+    state: SlackClientEventsUserState,
+) {
+    log::info!("socket mode connected: {:?}", event);
+    set_connected(&state, true);
 }
 
 async fn push_events_dispatcher(
     event: SlackPushEventCallback,
     _client: Arc<SlackHyperClient>,
     state: SlackClientEventsUserState,
-) -> Result<(), Box<(dyn std::error::Error + Send + Sync + 'static)>> {
+) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
     // process only messages here
     let message = match &event.event {
-        SlackEventCallbackBody::Message(event) if event.subtype.is_none() => event.clone(),
+        SlackEventCallbackBody::Message(event) => event.clone(),
         _ => return Ok(()),
     };
-    let context_lock = state.read().await;
-    let bot_state = match context_lock.get_user_state::<Arc<BotState>>() {
-        Some(state) => state.clone(),
+    let bot_state = match get_bot_state(&state, Some(&event.team_id)).await {
+        Some(bot_state) => bot_state,
+        None => return Ok(()),
+    };
+
+    if bot_state.dedup.is_duplicate(&event.event_id, message.origin.channel.as_ref(), Some(&message.origin.ts)) {
+        log::info!("duplicate event dropped: event_id='{}', ts='{}'", event.event_id, message.origin.ts);
+        bot_state.metrics.dropped_duplicate();
+        return Ok(());
+    }
+
+    let message = match &message.subtype {
+        None => message,
+        Some(SlackMessageEventType::MessageChanged) if bot_state.settings.rerun_on_edit => {
+            match edited_message(&message, &bot_state).await? {
+                Some(message) => message,
+                None => return Ok(()),
+            }
+        }
+        _ => return Ok(()),
+    };
+
+    let msg_body = extract_msg_body(&message)?;
+
+    // plain messages go to handlers with message_pattern
+    let msg_body = match bot_state.strip_command_marker(&msg_body) {
+        Some(msg_body) => msg_body.to_string(),
         None => {
-            log::error!("Bot state is missing");
+            spawn_message_handlers(&msg_body, message, bot_state)?;
             return Ok(());
         }
     };
 
-    let msg_body = extract_msg_body(&message)?;
+    log::debug!("got new push event: {:?}", &event);
+    spawn_command(msg_body, message, bot_state)?;
+    Ok(())
+}
+
+async fn commands_dispatcher(
+    event: SlackCommandEvent,
+    _client: Arc<SlackHyperClient>,
+    state: SlackClientEventsUserState,
+) -> Result<SlackCommandEventResponse, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    log::debug!("got new command: {:?}", &event);
+    let bot_state = match get_bot_state(&state, Some(&event.team_id)).await {
+        Some(bot_state) => bot_state,
+        None => return Err("Bot state is missing".into()),
+    };
 
-    // ignore non-bot messages
-    // TODO implement free_reply handler for the other cases
-    if !msg_body.starts_with(&bot_state.bot_marker) {
-        log::trace!("event was ignored as non-related to the bot");
+    // slash command has no message to reply to, so post one to keep the conversation in its thread
+    let msg_body = event.text.unwrap_or_default().trim().to_string();
+    let echo_msg = format!("<@{}> used `{} {}`", event.user_id, event.command, msg_body);
+    let msg_ts = bot_state.slack_cli.send_msg_impl(&event.channel_id, None, &echo_msg).await?;
+
+    let origin = SlackMessageOrigin::new(msg_ts).with_channel(event.channel_id);
+    let mut message = SlackMsgEv::new(origin, SlackMessageSender::new().with_user(event.user_id));
+    message.content = Some(SlackMessageContent::new().with_text(format!("{} {}", bot_state.bot_marker, msg_body)));
+    spawn_command(msg_body, message, bot_state)?;
+
+    let response_content = SlackMessageContent::new().with_text("Working on it".into());
+    Ok(SlackCommandEventResponse::new(response_content).with_response_type(SlackMessageResponseType::Ephemeral))
+}
+
+// slack expects the response in 3 seconds, so handlers run in background and modals are closed on submit
+async fn interactions_dispatcher(
+    event: SlackInteractionEvent,
+    _client: Arc<SlackHyperClient>,
+    state: SlackClientEventsUserState,
+) -> Result<SlackInteractionResponse, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    log::trace!("got new interaction event: {:?}", event);
+    let bot_state = match get_bot_state(&state, interaction_team(&event)).await {
+        Some(bot_state) => bot_state,
+        None => return Ok(SlackInteractionResponse::Empty),
+    };
+    let handler = match interaction_handler_name(&event).and_then(|name| bot_state.get_handler_by_name(name)) {
+        Some(handler) => handler,
+        None => {
+            log::debug!("interaction event was ignored as non-related to handlers");
+            return Ok(SlackInteractionResponse::Empty);
+        }
+    };
+
+    let invocation_id = new_invocation_id();
+    let channel_id = match &event {
+        SlackInteractionEvent::BlockActions(event) => event.channel.as_ref().map(|channel| channel.id.clone()),
+        _ => None,
+    };
+    let span = invocation_span(&invocation_id, &channel_id.unwrap_or("-".into()), interaction_user(&event));
+    span.record("handler", handler.name());
+    let task = async move {
+        if let Err(err) = handler.handle_interaction(&event, &bot_state).await {
+            log::error!("handler '{}' failed to handle interaction: {:#}", handler.name(), err);
+        }
+    };
+    tokio::spawn(with_invocation_id(invocation_id, task).instrument(span));
+    Ok(SlackInteractionResponse::Empty)
+}
+
+fn spawn_command(msg_body: String, message: SlackMsgEv, bot_state: Arc<BotState>) -> Result<()> {
+    let (channel_id, thread_ts) = extract_channel_thread(&message)?;
+    let msg_ts = extract_msg_ts(&message);
+    let invocation_id = new_invocation_id();
+    let span = invocation_span(&invocation_id, &channel_id, message.sender.user.as_ref());
+
+    let command = async move {
+        let dispatch = dispatch_command(msg_body, message, bot_state.clone(), channel_id.clone(), thread_ts.clone());
+        match bot_state.settings.rerun_on_edit {
+            true => bot_state.replies.track(&channel_id, &thread_ts, &msg_ts, dispatch).await,
+            false => dispatch.await,
+        }
+    };
+    tokio::spawn(with_invocation_id(invocation_id, command).instrument(span));
+    Ok(())
+}
+
+// bot messages are skipped, so the bot doesn't react to its own replies
+fn spawn_message_handlers(msg_body: &str, message: SlackMsgEv, bot_state: Arc<BotState>) -> Result<()> {
+    if message.sender.bot_id.is_some() {
         return Ok(());
     }
+    let channel_id = extract_channel_id(&message)?;
+    for handler in bot_state.get_message_handlers(&channel_id) {
+        let mut matches: Vec<String> = vec![];
+        for found in handler.message_pattern().into_iter().flat_map(|pattern| pattern.find_iter(msg_body)) {
+            if !matches.iter().any(|known| known == found.as_str()) {
+                matches.push(found.as_str().to_string());
+            }
+        }
+        if matches.is_empty() {
+            continue;
+        }
 
-    log::debug!("got new push event: {:?}", &event);
+        let invocation_id = new_invocation_id();
+        let span = invocation_span(&invocation_id, &channel_id, message.sender.user.as_ref());
+        span.record("handler", handler.name());
+        let (message, bot_state) = (message.clone(), bot_state.clone());
+        let task = async move {
+            log::debug!("message matched by handler '{}': {:?}", handler.name(), matches);
+            let started = Instant::now();
+            let result = handler.handle_message(&matches, &message, &bot_state).await;
+            audit_command(handler.name(), &matches, &message, &bot_state, started.elapsed(), outcome(&result)).await;
+            if let Err(err) = result {
+                log::warn!("handler '{}' failed to handle message: {:#}", handler.name(), err);
+            }
+        };
+        tokio::spawn(with_invocation_id(invocation_id, task).instrument(span));
+    }
+    Ok(())
+}
 
-    let msg_body = msg_body.strip_prefix(&bot_state.bot_marker).unwrap().trim();
-    let (channel_id, thread_ts) = extract_channel_thread(&message)?;
+// turns message_changed event into the edited message, if its text was changed
+async fn edited_message(event: &SlackMsgEv, bot_state: &BotState) -> Result<Option<SlackMsgEv>> {
+    let edited = match &event.message {
+        Some(edited) => edited,
+        None => return Ok(None),
+    };
+    let text = edited.content.as_ref().and_then(|content| content.text.as_ref());
+    let prev_text =
+        event.previous_message.as_ref().and_then(|prev| prev.content.as_ref()).and_then(|c| c.text.as_ref());
+
+    // message_changed is also sent on thread replies, unfurls and other non-text updates
+    match text {
+        Some(text) if Some(text) != prev_text && bot_state.strip_command_marker(text).is_some() => {}
+        _ => return Ok(None),
+    }
+
+    // edited message doesn't carry thread_ts, so look it up
+    let channel_id = extract_channel_id(event)?;
+    let thread_ts = match bot_state.slack_cli.get_msg(&channel_id, &edited.ts).await? {
+        Some(msg) => extract_thread_ts(&msg),
+        None => edited.ts.clone(),
+    };
+
+    let origin = SlackMessageOrigin::new(edited.ts.clone()).with_channel(channel_id).with_thread_ts(thread_ts);
+    let mut message = SlackMsgEv::new(origin, edited.sender.clone());
+    message.content = edited.content.clone();
+    Ok(Some(message))
+}
+
+async fn dispatch_command(
+    msg_body: String,
+    message: SlackMsgEv,
+    bot_state: Arc<BotState>,
+    channel_id: SlackChannelId,
+    thread_ts: SlackTs,
+) {
     let handler_name = msg_body.split(' ').next().unwrap_or("help").to_string();
-    let args = match shlex::split(msg_body) {
+    Span::current().record("handler", handler_name.as_str());
+    log::debug!("command '{handler_name}' received");
+
+    let args = match shlex::split(&msg_body) {
         Some(args) => args,
         None => {
             let err_msg = "Fail to parse arguments: Invalid quoting";
-            return Ok(bot_state.slack_cli.send_reply(&channel_id, &thread_ts, err_msg).await?);
+            if let Err(err) = bot_state.slack_cli.send_reply(&channel_id, &thread_ts, err_msg).await {
+                log::error!("Failed to send error message to slack: {:#?}", err);
+            }
+            return;
         }
     };
 
-    if let Some(handler) = bot_state.get_msg_handler(&channel_id, &handler_name) {
-        tokio::spawn(async move { execute_handler(handler, args, message, bot_state, channel_id, thread_ts).await });
-    } else {
-        tokio::spawn(async move {
-            if let Err(err) = bot_state.help_handler.handle(&handler_name, &message, &bot_state).await {
-                log::error!("Failed to send error message to slack: {:#?}", err);
+    let builtin_available = bot_state.is_builtin_available(&channel_id, &handler_name);
+    if let (RELOAD_COMMAND, Some(reloader), true) = (handler_name.as_str(), &bot_state.reloader, builtin_available) {
+        let command = handle_reload_command(reloader, &message, &bot_state);
+        run_builtin(RELOAD_COMMAND, &args, &message, &bot_state, &channel_id, &thread_ts, command).await;
+        return;
+    }
+
+    if handler_name == CONFIG_COMMAND && builtin_available {
+        let command = handle_config_command(&args, &message, &bot_state);
+        run_builtin(CONFIG_COMMAND, &args, &message, &bot_state, &channel_id, &thread_ts, command).await;
+        return;
+    }
+
+    let handler = bot_state.get_msg_handler(&channel_id, &handler_name);
+    let reply_help = match bot_state.settings.help_mode {
+        HelpMode::Full => true,
+        HelpMode::ExplicitOnly => handler_name.is_empty() || handler_name == bot_state.handlers().help.name(),
+        HelpMode::Disabled => false,
+    };
+    if handler.is_none() && !reply_help {
+        log::debug!("unknown command '{handler_name}' was ignored");
+        return;
+    }
+    run_handler(&handler_name, handler, &args, &message, &bot_state, &channel_id, &thread_ts).await;
+}
+
+// built-in commands skip middlewares and queues, but are audited like handlers
+async fn run_builtin(
+    name: &str,
+    args: &[String],
+    message: &SlackMsgEv,
+    bot_state: &BotState,
+    channel_id: &SlackChannelId,
+    thread_ts: &SlackTs,
+    command: impl Future<Output = Result<()>>,
+) {
+    let started = Instant::now();
+    let result = command.await;
+    audit_command(name, args, message, bot_state, started.elapsed(), outcome(&result)).await;
+    if let Err(err) = &result {
+        reply_failure(bot_state, channel_id, thread_ts, name, err).await;
+    }
+}
+
+// runs the handler (or help if None) with middlewares, queues, metrics, audit and error replies
+pub(crate) async fn run_handler(
+    handler_name: &str,
+    handler: Option<ArcMsgHandler>,
+    args: &[String],
+    message: &SlackMsgEv,
+    bot_state: &BotState,
+    channel_id: &SlackChannelId,
+    thread_ts: &SlackTs,
+) {
+    for middleware in &bot_state.middlewares {
+        match middleware.before(handler_name, args, message, bot_state).await {
+            Ok(true) => {}
+            Ok(false) => {
+                log::debug!("command '{handler_name}' was stopped by middleware");
+                audit_command(handler_name, args, message, bot_state, Duration::ZERO, STOPPED_OUTCOME).await;
+                return;
+            }
+            Err(err) => {
+                let result = Err(err);
+                audit_command(handler_name, args, message, bot_state, Duration::ZERO, outcome(&result)).await;
+                if let Err(err) = &result {
+                    reply_failure(bot_state, channel_id, thread_ts, handler_name, err).await;
+                }
+                return;
             }
-        });
+        }
+    }
+
+    let handlers = bot_state.handlers();
+    let result = match handler {
+        Some(handler) => match acquire_permits(handler.name(), &handlers, bot_state, channel_id, thread_ts).await {
+            Ok(_permits) => execute_handler(handler, args, message, bot_state, channel_id, thread_ts).await,
+            Err(err) => {
+                log::error!("Fail to queue command '{}': {:#}", handler.name(), err);
+                reply_failure(bot_state, channel_id, thread_ts, handler.name(), &err).await;
+                Err(err)
+            }
+        },
+        None => {
+            let started = Instant::now();
+            let result = handlers.help.handle(handler_name, message, bot_state).await;
+            let help_name = handlers.help.name();
+            audit_command(help_name, args, message, bot_state, started.elapsed(), outcome(&result)).await;
+            if let Err(err) = &result {
+                log::error!("Failed to send help message to slack: {:#?}", err);
+            }
+            result
+        }
+    };
+
+    for middleware in &bot_state.middlewares {
+        middleware.after(handler_name, message, bot_state, &result).await;
+    }
+}
+
+// help and admin commands are cheap, so only handlers are limited.
+// Permits of the handler queue and the global one are held until the handler finishes
+async fn acquire_permits<'a>(
+    handler_name: &str,
+    handlers: &'a Handlers,
+    bot_state: &'a BotState,
+    channel_id: &SlackChannelId,
+    thread_ts: &SlackTs,
+) -> Result<Vec<SemaphorePermit<'a>>> {
+    let notify_queued = |position| notify_queued(bot_state, channel_id, thread_ts, position);
+    let queues = [handlers.queues.get(handler_name), bot_state.command_queue.as_deref()];
+    let mut permits = Vec::new();
+    for queue in queues.into_iter().flatten() {
+        permits.push(queue.acquire(&bot_state.metrics, notify_queued).await?);
+    }
+    Ok(permits)
+}
+
+async fn notify_queued(bot_state: &BotState, channel_id: &SlackChannelId, thread_ts: &SlackTs, position: usize) {
+    let msg = format!("Queued, position {position}");
+    if let Err(err) = bot_state.slack_cli.send_reply(channel_id, thread_ts, &msg).await {
+        log::error!("Failed to send queued message to slack: {:#?}", err);
     }
-    Ok(())
 }
 
 async fn execute_handler(
     handler: ArcMsgHandler,
-    args: Vec<String>,
-    msg_ev: SlackMsgEv,
-    bot_state: Arc<BotState>,
-    channel_id: SlackChannelId,
-    thread_ts: SlackTs,
-) {
-    match handler.handle(&args, &msg_ev, &bot_state).await {
+    args: &[String],
+    msg_ev: &SlackMsgEv,
+    bot_state: &BotState,
+    channel_id: &SlackChannelId,
+    thread_ts: &SlackTs,
+) -> Result<()> {
+    bot_state.metrics.handler_started();
+    let started = Instant::now();
+    let result = handler.handle(args, msg_ev, bot_state).await;
+    bot_state.metrics.handler_finished();
+
+    let channel_name = channel_name(bot_state, channel_id);
+    bot_state.metrics.observe_command(handler.name(), &channel_name, started.elapsed(), &result);
+    audit_command(handler.name(), args, msg_ev, bot_state, started.elapsed(), outcome(&result)).await;
+    match &result {
         Ok(_) => log::debug!("handler {} finished successfully", handler.name()),
-        Err(err) => {
-            log::error!("handler failed with error: {:#?}", err);
-            let error_slack_msg = "Error occurred during handling. Check logs for details.";
-            if let Err(err) = bot_state.slack_cli.send_reply(&channel_id, &thread_ts, error_slack_msg).await {
-                log::error!("Failed to send error message to slack: {:#?}", err);
-            }
+        Err(err) => reply_failure(bot_state, channel_id, thread_ts, handler.name(), err).await,
+    }
+    result
+}
+
+// channel name, or id if the channel is unknown
+fn channel_name(bot_state: &BotState, channel_id: &SlackChannelId) -> String {
+    let channel_name = bot_state.known_channels.get(channel_id).map(|name| name.value().clone());
+    channel_name.unwrap_or_else(|| channel_id.to_string())
+}
+
+async fn audit_command(
+    handler_name: &str,
+    args: &[String],
+    msg_ev: &SlackMsgEv,
+    bot_state: &BotState,
+    duration: Duration,
+    outcome: &str,
+) {
+    if bot_state.audit_sinks.is_empty() {
+        return;
+    }
+    let (channel, thread_permalink) = match extract_channel_thread(msg_ev) {
+        Ok((channel, thread)) => {
+            (channel_name(bot_state, &channel), bot_state.slack_cli.get_permalink(&channel, &thread).await.ok())
+        }
+        Err(_) => ("-".to_string(), None),
+    };
+    let record = AuditRecord {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        invocation_id: current_invocation_id(),
+        user: msg_ev.sender.user.as_ref().map(|user| user.to_string()),
+        channel,
+        thread_permalink,
+        handler: handler_name.to_string(),
+        args: args.to_vec(),
+        outcome: outcome.to_string(),
+        duration_ms: duration.as_millis() as u64,
+    };
+    for sink in &bot_state.audit_sinks {
+        if let Err(err) = sink.record(&record).await {
+            log::error!("Fail to write audit record: {:#?}", err);
         }
     }
 }
 
+async fn reply_failure(
+    bot_state: &BotState,
+    channel_id: &SlackChannelId,
+    thread_ts: &SlackTs,
+    handler_name: &str,
+    err: &anyhow::Error,
+) {
+    let error_slack_msg = match err.downcast_ref::<HandlerError>() {
+        Some(HandlerError::User(msg) | HandlerError::Unauthorized(msg)) => {
+            log::info!("handler {handler_name} rejected the command: {msg}");
+            msg.clone()
+        }
+        Some(err @ HandlerError::Upstream { .. }) => {
+            log::warn!("handler {handler_name} failed with upstream error: {err}");
+            err.to_string()
+        }
+        _ => {
+            let correlation_id = current_invocation_id().unwrap_or_else(new_invocation_id);
+            log::error!("handler {handler_name} failed with error, correlation_id={correlation_id}: {:#?}", err);
+            format!("{} (error id: `{correlation_id}`)", bot_state.settings.error_message)
+        }
+    };
+    if let Err(err) = bot_state.slack_cli.send_reply(channel_id, thread_ts, &error_slack_msg).await {
+        log::error!("Failed to send error message to slack: {:#?}", err);
+    }
+}
+
+// socket errors are followed by a reconnect, so the bot is not ready until the next hello
 fn error_handler(
     err: Box<dyn std::error::Error + Send + Sync>,
     _: Arc<SlackHyperClient>,
-    _: SlackClientEventsUserState,
+    state: SlackClientEventsUserState,
 ) -> HttpStatusCode {
     log::error!("{:#?}", err);
+    if let Some(SlackClientError::SocketModeProtocolError(_)) = err.downcast_ref::<SlackClientError>() {
+        set_connected(&state, false);
+    }
     HttpStatusCode::OK
 }
 
 // inspired by https://github.com/abdolence/slack-morphism-rust/blob/master/examples/socket_mode.rs
-
-// async fn interactions_dispatcher(
-//     event: SlackInteractionEvent,
-//     _client: Arc<SlackHyperClient>,
-//     _states: SlackClientEventsUserState,
-// ) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
-//     log::trace!("got new interaction event: {:#?}", event);
-//     Ok(())
-// }
-
-// async fn commands_dispatcher(
-//     event: SlackCommandEvent,
-//     client: Arc<SlackHyperClient>,
-//     _states: SlackClientEventsUserState,
-// ) -> std::result::Result<SlackCommandEventResponse, Box<dyn std::error::Error + Send + Sync>> {
-//     log::trace!("got new command: {:#?}", event);
-//
-//     let token_value: SlackApiTokenValue = config_env_var("SLACK_TEST_TOKEN")?.into();
-//     let token = SlackApiToken::new(token_value);
-//
-//     // Sessions are lightweight and basically just a reference to client and token
-//     let session = client.open_session(&token);
-//
-//     session.api_test(&SlackApiTestRequest::new().with_foo("Test".into())).await?;
-//
-//     let user_info_resp = session.users_info(&SlackApiUsersInfoRequest::new(event.user_id.clone())).await?;
-//
-//     println!("{:#?}", user_info_resp);
-//
-//     Ok(SlackCommandEventResponse::new(SlackMessageContent::new().with_text(
-//         format!("Working on it: {:#?}", user_info_resp.user.team_id),
-//     )))
-// }
@@ -0,0 +1,99 @@
+use super::{commands_dispatcher, error_handler, get_bot_state, interactions_dispatcher, push_events_dispatcher};
+use crate::workspaces::Workspaces;
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Extension, Json, Router};
+use slack_morphism::prelude::{
+    SlackCommandEvent, SlackEventsAxumListener, SlackEventsExtractors, SlackHyperHttpsConnector,
+    SlackHyperListenerEnvironment, SlackInteractionEvent, SlackPushEvent,
+};
+use slack_morphism::SlackSigningSecret;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+const EVENTS_PATH: &str = "/slack/events";
+const COMMANDS_PATH: &str = "/slack/commands";
+const INTERACTIONS_PATH: &str = "/slack/interactions";
+
+pub(super) async fn serve_http(
+    listener_env: Arc<SlackHyperListenerEnvironment>,
+    bind_addr: SocketAddr,
+    signing_secret: &str,
+    workspaces: &Workspaces,
+) -> Result<()> {
+    let listener: SlackEventsAxumListener<SlackHyperHttpsConnector> = SlackEventsAxumListener::new(listener_env);
+    let signing_secret = SlackSigningSecret::new(signing_secret.into());
+
+    let app = Router::new()
+        .route(
+            EVENTS_PATH,
+            post(push_event).layer(
+                listener.events_layer(&signing_secret).with_event_extractor(SlackEventsExtractors::push_event()),
+            ),
+        )
+        .route(
+            COMMANDS_PATH,
+            post(command_event).layer(
+                listener.events_layer(&signing_secret).with_event_extractor(SlackEventsExtractors::command_event()),
+            ),
+        )
+        .route(
+            INTERACTIONS_PATH,
+            post(interaction_event).layer(
+                listener.events_layer(&signing_secret).with_event_extractor(SlackEventsExtractors::interaction_event()),
+            ),
+        );
+
+    log::info!("listening for slack events on http://{bind_addr}");
+    let tcp_listener = tokio::net::TcpListener::bind(bind_addr).await?;
+    workspaces.all().for_each(|workspace| workspace.health.set_connected(true));
+    axum::serve(tcp_listener, app).await?;
+    Ok(())
+}
+
+async fn push_event(
+    Extension(env): Extension<Arc<SlackHyperListenerEnvironment>>,
+    Extension(event): Extension<SlackPushEvent>,
+) -> Response {
+    match event {
+        SlackPushEvent::UrlVerification(verification) => verification.challenge.into_response(),
+        SlackPushEvent::EventCallback(callback) => {
+            // ack right away, slack retries events which are not acknowledged within 3 seconds
+            tokio::spawn(async move {
+                if let Err(err) = push_events_dispatcher(callback, env.client.clone(), env.user_state.clone()).await {
+                    error_handler(err, env.client.clone(), env.user_state.clone());
+                }
+            });
+            StatusCode::OK.into_response()
+        }
+        SlackPushEvent::AppRateLimited(event) => {
+            log::warn!("slack events are rate limited: {:?}", event);
+            if let Some(bot_state) = get_bot_state(&env.user_state, None).await {
+                bot_state.metrics.rate_limited();
+            }
+            StatusCode::OK.into_response()
+        }
+    }
+}
+
+async fn command_event(
+    Extension(env): Extension<Arc<SlackHyperListenerEnvironment>>,
+    Extension(event): Extension<SlackCommandEvent>,
+) -> Response {
+    match commands_dispatcher(event, env.client.clone(), env.user_state.clone()).await {
+        Ok(response) => Json(response).into_response(),
+        Err(err) => error_handler(err, env.client.clone(), env.user_state.clone()).into_response(),
+    }
+}
+
+async fn interaction_event(
+    Extension(env): Extension<Arc<SlackHyperListenerEnvironment>>,
+    Extension(event): Extension<SlackInteractionEvent>,
+) -> Response {
+    match interactions_dispatcher(event, env.client.clone(), env.user_state.clone()).await {
+        Ok(response) => response.into_response(),
+        Err(err) => error_handler(err, env.client.clone(), env.user_state.clone()).into_response(),
+    }
+}
@@ -0,0 +1,111 @@
+use crate::error::outcome;
+use anyhow::Result;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use slack_morphism::errors::SlackClientError;
+use std::time::Duration;
+
+// Prometheus metrics of the bot, exported by ops server if `metrics_addr` is configured
+pub(crate) struct Metrics {
+    registry: Registry,
+    // handler, channel, outcome
+    commands: IntCounterVec,
+    // handler
+    handler_latency: HistogramVec,
+    handlers_in_flight: IntGauge,
+    // queue: global or handler name
+    queue_depth: IntGaugeVec,
+    // method
+    slack_api_calls: IntCounterVec,
+    // method
+    slack_api_errors: IntCounterVec,
+    slack_rate_limited: IntCounter,
+    dropped_duplicates: IntCounter,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new_custom(Some("slack_cmd".into()), None)?;
+        let commands =
+            IntCounterVec::new(Opts::new("commands_total", "Handled commands"), &["handler", "channel", "outcome"])?;
+        let handler_latency =
+            HistogramVec::new(HistogramOpts::new("handler_duration_seconds", "Handler execution time"), &["handler"])?;
+        let handlers_in_flight = IntGauge::new("handlers_in_flight", "Handlers being executed")?;
+        let queue_depth = IntGaugeVec::new(Opts::new("queue_depth", "Commands waiting for a free slot"), &["queue"])?;
+        let slack_api_calls = IntCounterVec::new(Opts::new("slack_api_calls_total", "Slack API calls"), &["method"])?;
+        let slack_api_errors =
+            IntCounterVec::new(Opts::new("slack_api_errors_total", "Failed Slack API calls"), &["method"])?;
+        let slack_rate_limited = IntCounter::new("slack_rate_limited_total", "Slack API calls hit rate limit")?;
+        let dropped_duplicates = IntCounter::new("dropped_duplicates_total", "Redelivered events dropped")?;
+
+        registry.register(Box::new(commands.clone()))?;
+        registry.register(Box::new(handler_latency.clone()))?;
+        registry.register(Box::new(handlers_in_flight.clone()))?;
+        registry.register(Box::new(queue_depth.clone()))?;
+        registry.register(Box::new(slack_api_calls.clone()))?;
+        registry.register(Box::new(slack_api_errors.clone()))?;
+        registry.register(Box::new(slack_rate_limited.clone()))?;
+        registry.register(Box::new(dropped_duplicates.clone()))?;
+
+        Ok(Self {
+            registry,
+            commands,
+            handler_latency,
+            handlers_in_flight,
+            queue_depth,
+            slack_api_calls,
+            slack_api_errors,
+            slack_rate_limited,
+            dropped_duplicates,
+        })
+    }
+
+    pub fn observe_command(&self, handler: &str, channel: &str, duration: Duration, result: &Result<()>) {
+        self.commands.with_label_values(&[handler, channel, outcome(result)]).inc();
+        self.handler_latency.with_label_values(&[handler]).observe(duration.as_secs_f64());
+    }
+
+    pub fn handler_started(&self) {
+        self.handlers_in_flight.inc();
+    }
+
+    pub fn handler_finished(&self) {
+        self.handlers_in_flight.dec();
+    }
+
+    pub fn queue_depth_inc(&self, queue: &str) {
+        self.queue_depth.with_label_values(&[queue]).inc();
+    }
+
+    pub fn queue_depth_dec(&self, queue: &str) {
+        self.queue_depth.with_label_values(&[queue]).dec();
+    }
+
+    pub fn observe_slack_call<T>(&self, method: &str, result: &Result<T, SlackClientError>) {
+        self.slack_api_calls.with_label_values(&[method]).inc();
+        match result {
+            Ok(_) => {}
+            Err(SlackClientError::RateLimitError(_)) => {
+                self.slack_rate_limited.inc();
+                self.slack_api_errors.with_label_values(&[method]).inc();
+            }
+            Err(_) => self.slack_api_errors.with_label_values(&[method]).inc(),
+        }
+    }
+
+    pub fn rate_limited(&self) {
+        self.slack_rate_limited.inc();
+    }
+
+    pub fn dropped_duplicate(&self) {
+        self.dropped_duplicates.inc();
+    }
+
+    // prometheus text format
+    pub fn render(&self) -> Result<String> {
+        let mut buffer = vec![];
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
@@ -0,0 +1,23 @@
+use crate::state::BotState;
+use crate::SlackMsgEv;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+// Hooks called around every command, in the registration order
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    // return false to stop processing of the command
+    async fn before(
+        &self,
+        _handler_name: &str,
+        _args: &[String],
+        _msg_ev: &SlackMsgEv,
+        _state: &BotState,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+
+    async fn after(&self, _handler_name: &str, _msg_ev: &SlackMsgEv, _state: &BotState, _result: &Result<()>) {}
+}
+pub type ArcMiddleware = Arc<dyn Middleware>;
@@ -0,0 +1,110 @@
+use crate::error::HandlerError;
+use crate::health::Health;
+use crate::invocation::{new_invocation_id, with_invocation_id};
+use crate::metrics::Metrics;
+use crate::webhook::WebhookRequest;
+use crate::workspaces::Workspaces;
+use anyhow::Result;
+use axum::body::Bytes;
+use axum::extract::{Path, Query};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Router};
+use prometheus::{Encoder, TextEncoder};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, OnceLock};
+
+const METRICS_PATH: &str = "/metrics";
+const HEALTHZ_PATH: &str = "/healthz";
+const READYZ_PATH: &str = "/readyz";
+const HOOKS_PATH: &str = "/hooks/{handler}";
+
+// workspaces are set once the bot is started, webhooks are rejected until then
+pub(crate) type WorkspacesCell = Arc<OnceLock<Arc<Workspaces>>>;
+
+// Serves metrics, liveness/readiness probes and handler webhooks, separately from slack transport
+pub(crate) async fn serve_ops(
+    bind_addr: SocketAddr,
+    metrics: Arc<Metrics>,
+    health: Arc<Health>,
+    workspaces: WorkspacesCell,
+) -> Result<()> {
+    let app = Router::new()
+        .route(METRICS_PATH, get(metrics_endpoint))
+        .route(HEALTHZ_PATH, get(healthz_endpoint))
+        .route(READYZ_PATH, get(readyz_endpoint))
+        .route(HOOKS_PATH, post(hooks_endpoint))
+        .layer(Extension(metrics))
+        .layer(Extension(health))
+        .layer(Extension(workspaces));
+    log::info!("serving {METRICS_PATH}, {HEALTHZ_PATH}, {READYZ_PATH} and {HOOKS_PATH} on http://{bind_addr}");
+    let tcp_listener = tokio::net::TcpListener::bind(bind_addr).await?;
+    axum::serve(tcp_listener, app).await?;
+    Ok(())
+}
+
+async fn metrics_endpoint(Extension(metrics): Extension<Arc<Metrics>>) -> Response {
+    match metrics.render() {
+        Ok(body) => ([(CONTENT_TYPE, TextEncoder::new().format_type().to_string())], body).into_response(),
+        Err(err) => {
+            log::error!("Fail to render metrics: {:#?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+// process is up
+async fn healthz_endpoint() -> Response {
+    "ok".into_response()
+}
+
+async fn hooks_endpoint(
+    Extension(workspaces): Extension<WorkspacesCell>,
+    Path(handler_name): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let workspaces = match workspaces.get() {
+        Some(workspaces) => workspaces.clone(),
+        None => return (StatusCode::SERVICE_UNAVAILABLE, "not ready").into_response(),
+    };
+    let handler = match workspaces.primary().get_handler_by_name(&handler_name) {
+        Some(handler) => handler,
+        None => return (StatusCode::NOT_FOUND, format!("unknown handler '{handler_name}'")).into_response(),
+    };
+
+    let request = WebhookRequest { headers, query, body };
+    let invocation_id = new_invocation_id();
+    let result = with_invocation_id(invocation_id.clone(), handler.handle_webhook(&request, &workspaces)).await;
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(err) => match err.downcast_ref::<HandlerError>() {
+            Some(HandlerError::User(msg)) => (StatusCode::BAD_REQUEST, msg.clone()).into_response(),
+            Some(HandlerError::Unauthorized(msg)) => (StatusCode::UNAUTHORIZED, msg.clone()).into_response(),
+            _ => {
+                log::error!("webhook of handler '{handler_name}' failed, invocation_id={invocation_id}: {err:#}");
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        },
+    }
+}
+
+// bot info is loaded, channels are fetched and slack connection is alive in every workspace.
+// Until workspaces are set, only the primary one is loading
+async fn readyz_endpoint(
+    Extension(health): Extension<Arc<Health>>,
+    Extension(workspaces): Extension<WorkspacesCell>,
+) -> Response {
+    let pending = match workspaces.get() {
+        Some(workspaces) => workspaces.pending(),
+        None => health.pending().into_iter().map(String::from).collect(),
+    };
+    match pending.is_empty() {
+        true => "ok".into_response(),
+        false => (StatusCode::SERVICE_UNAVAILABLE, format!("not ready: {}", pending.join(", "))).into_response(),
+    }
+}
@@ -0,0 +1,139 @@
+use crate::metrics::Metrics;
+use anyhow::Result;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+pub(crate) const GLOBAL_QUEUE: &str = "global";
+
+// Limits commands running at the same time, the rest wait in FIFO order
+pub(crate) struct CommandQueue {
+    name: String,
+    permits: Semaphore,
+    waiting: AtomicUsize,
+}
+
+impl CommandQueue {
+    pub fn new(name: &str, limit: usize) -> Self {
+        Self {
+            name: name.into(),
+            permits: Semaphore::new(limit),
+            waiting: AtomicUsize::new(0),
+        }
+    }
+
+    // on_queued is called with 1-based position in the queue if all slots are busy
+    pub async fn acquire<F, Fut>(&self, metrics: &Metrics, on_queued: F) -> Result<SemaphorePermit<'_>>
+    where
+        F: FnOnce(usize) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        if let Ok(permit) = self.permits.try_acquire() {
+            return Ok(permit);
+        }
+        let (_waiting, position) = Waiting::new(self, metrics);
+        log::debug!("command is queued in '{}' queue, position: {position}", self.name);
+        on_queued(position).await;
+        Ok(self.permits.acquire().await?)
+    }
+}
+
+// keeps waiting counter and queue depth metric right if the command is cancelled while waiting
+struct Waiting<'a> {
+    queue: &'a CommandQueue,
+    metrics: &'a Metrics,
+}
+
+impl<'a> Waiting<'a> {
+    // returns 1-based position in the queue
+    fn new(queue: &'a CommandQueue, metrics: &'a Metrics) -> (Self, usize) {
+        let position = queue.waiting.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics.queue_depth_inc(&queue.name);
+        (Self { queue, metrics }, position)
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.queue.waiting.fetch_sub(1, Ordering::Relaxed);
+        self.metrics.queue_depth_dec(&self.queue.name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    async fn not_queued(position: usize) {
+        panic!("unexpectedly queued at position {position}");
+    }
+
+    fn depth(metrics: &Metrics) -> String {
+        let rendered = metrics.render().unwrap();
+        let line = rendered.lines().find(|line| line.starts_with("slack_cmd_queue_depth{"));
+        line.unwrap_or_default().to_string()
+    }
+
+    #[tokio::test]
+    async fn limits_running_commands() {
+        let (queue, metrics) = (CommandQueue::new("q", 2), Metrics::new().unwrap());
+        let first = queue.acquire(&metrics, not_queued).await.unwrap();
+        let _second = queue.acquire(&metrics, not_queued).await.unwrap();
+
+        let third = timeout(Duration::from_millis(20), queue.acquire(&metrics, |_| async {}));
+        assert!(third.await.is_err());
+
+        drop(first);
+        assert!(queue.acquire(&metrics, not_queued).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn notifies_queued_positions() {
+        let (queue, metrics) = (Arc::new(CommandQueue::new("q", 1)), Arc::new(Metrics::new().unwrap()));
+        let running = queue.acquire(&metrics, not_queued).await.unwrap();
+
+        let (positions_tx, mut positions) = tokio::sync::mpsc::unbounded_channel();
+        let mut waiters = Vec::new();
+        for expected in [1, 2] {
+            let (queue, metrics, positions_tx) = (queue.clone(), metrics.clone(), positions_tx.clone());
+            waiters.push(tokio::spawn(async move {
+                let notify = move |position| {
+                    positions_tx.send(position).unwrap();
+                    async {}
+                };
+                let _permit = queue.acquire(&metrics, notify).await.unwrap();
+            }));
+            assert_eq!(positions.recv().await, Some(expected));
+        }
+        assert_eq!(depth(&metrics), r#"slack_cmd_queue_depth{queue="q"} 2"#);
+
+        drop(running);
+        for waiter in waiters {
+            waiter.await.unwrap();
+        }
+        assert_eq!(depth(&metrics), r#"slack_cmd_queue_depth{queue="q"} 0"#);
+    }
+
+    #[tokio::test]
+    async fn cancelled_waiter_leaves_queue() {
+        let (queue, metrics) = (CommandQueue::new("q", 1), Metrics::new().unwrap());
+        let _running = queue.acquire(&metrics, not_queued).await.unwrap();
+
+        let cancelled = timeout(Duration::from_millis(20), queue.acquire(&metrics, |_| async {}));
+        assert!(cancelled.await.is_err());
+        assert_eq!(queue.waiting.load(Ordering::Relaxed), 0);
+        assert_eq!(depth(&metrics), r#"slack_cmd_queue_depth{queue="q"} 0"#);
+
+        // the next waiter takes the freed position
+        let (position_tx, position_rx) = tokio::sync::oneshot::channel();
+        let notify = move |position| {
+            position_tx.send(position).unwrap();
+            async {}
+        };
+        assert!(timeout(Duration::from_millis(20), queue.acquire(&metrics, notify)).await.is_err());
+        assert_eq!(position_rx.await, Ok(1));
+    }
+}
@@ -0,0 +1,78 @@
+use crate::builder::validate_handlers;
+use crate::config::{BotConfig, HandlerRegistry};
+use crate::state::BotState;
+use crate::utils::extract_channel_thread;
+use crate::SlackMsgEv;
+use anyhow::Result;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub(crate) const RELOAD_COMMAND: &str = "reload";
+
+// Re-creates handlers from the config file the bot was built from.
+// Only `handlers` section is applied, other changes require restart.
+pub(crate) struct ConfigReloader {
+    path: PathBuf,
+    registry: Arc<HandlerRegistry>,
+}
+
+impl ConfigReloader {
+    pub fn new(path: PathBuf, registry: Arc<HandlerRegistry>) -> Self {
+        Self { path, registry }
+    }
+
+    pub async fn reload(&self, state: &BotState) -> Result<usize> {
+        // reading the file is blocking, so it is kept off the runtime workers
+        let path = self.path.clone();
+        let config = tokio::task::spawn_blocking(move || BotConfig::from_file(&path)).await??;
+        let (handlers, limits) = self.registry.create_all(&config.handlers)?;
+        validate_handlers(&handlers)?;
+
+        let handlers_count = handlers.len();
+        state.set_handlers(handlers, &limits);
+        log::info!("{} handlers were reloaded from {}", handlers_count, self.path.display());
+        Ok(handlers_count)
+    }
+}
+
+// `reload` command, available for bot admins only
+pub(crate) async fn handle_reload_command(
+    reloader: &ConfigReloader,
+    msg_ev: &SlackMsgEv,
+    state: &BotState,
+) -> Result<()> {
+    let (channel, thread) = extract_channel_thread(msg_ev)?;
+    let is_admin = msg_ev.sender.user.as_ref().is_some_and(|user| state.settings.admins.contains(&user.to_string()));
+    let reply = match is_admin {
+        true => match reloader.reload(state).await {
+            Ok(handlers_count) => format!("Reloaded {handlers_count} handlers"),
+            Err(err) => format!("Fail to reload handlers: {err:#}"),
+        },
+        false => format!("Only bot admins can use `{RELOAD_COMMAND}`"),
+    };
+    state.slack_cli.send_reply(&channel, &thread, &reply).await
+}
+
+#[cfg(unix)]
+pub(crate) fn reload_on_sighup(state: Arc<BotState>) -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let Some(reloader) = state.reloader.clone() else {
+        return Ok(());
+    };
+    let mut hangups = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            log::info!("SIGHUP received, reloading handlers");
+            if let Err(err) = reloader.reload(&state).await {
+                log::error!("Fail to reload handlers: {:#?}", err);
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn reload_on_sighup(_: Arc<BotState>) -> Result<()> {
+    Ok(())
+}
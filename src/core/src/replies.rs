@@ -0,0 +1,231 @@
+use crate::slack_cli::SlackCli;
+use crate::SlackMsgHist;
+use anyhow::Result;
+use async_trait::async_trait;
+use dashmap::DashMap;
+use slack_morphism::prelude::{SlackBlock, SlackView};
+use slack_morphism::{
+    SlackBotInfo, SlackChannelId, SlackTeamId, SlackTriggerId, SlackTs, SlackUser, SlackUserId, SlackViewId,
+};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const REPLIES_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const REPLIES_CAPACITY: usize = 10_000;
+
+tokio::task_local! {
+    static CURRENT_INVOCATION: Arc<InvocationReplies>;
+}
+
+// replies posted to the invocation thread while handling a single command message
+struct InvocationReplies {
+    channel: SlackChannelId,
+    thread_ts: SlackTs,
+    // replies of the previous run, reused one by one
+    previous: Mutex<VecDeque<SlackTs>>,
+    posted: Mutex<Vec<SlackTs>>,
+}
+
+impl InvocationReplies {
+    fn is_same_thread(&self, channel: &SlackChannelId, thread_ts: Option<&SlackTs>) -> bool {
+        &self.channel == channel && thread_ts == Some(&self.thread_ts)
+    }
+}
+
+// Keeps invocation message -> bot replies mapping,
+// so re-running an edited command updates the replies instead of posting new ones.
+pub(crate) struct ReplyTracker {
+    // (channel_id, invocation_msg_ts) -> (last_run_time, replies)
+    replies: DashMap<(SlackChannelId, SlackTs), (Instant, Vec<SlackTs>)>,
+}
+
+impl ReplyTracker {
+    pub fn new() -> Self {
+        Self {
+            replies: DashMap::new(),
+        }
+    }
+
+    pub async fn track<F: Future>(
+        &self,
+        channel: &SlackChannelId,
+        thread_ts: &SlackTs,
+        msg_ts: &SlackTs,
+        fut: F,
+    ) -> F::Output {
+        let key = (channel.clone(), msg_ts.clone());
+        let previous = self.replies.remove(&key).map(|(_, (_, replies))| replies).unwrap_or_default();
+        let invocation = Arc::new(InvocationReplies {
+            channel: channel.clone(),
+            thread_ts: thread_ts.clone(),
+            previous: Mutex::new(previous.into()),
+            posted: Mutex::new(Vec::new()),
+        });
+
+        let result = CURRENT_INVOCATION.scope(invocation.clone(), fut).await;
+
+        let posted = std::mem::take(&mut *invocation.posted.lock().unwrap());
+        if !posted.is_empty() {
+            self.replies.insert(key, (Instant::now(), posted));
+        }
+        if self.replies.len() > REPLIES_CAPACITY {
+            self.replies.retain(|_, (last_run, _)| last_run.elapsed() < REPLIES_TTL);
+        }
+        result
+    }
+}
+
+// Wraps SlackCli to redirect replies of the tracked invocation to updates of its previous replies.
+pub(crate) struct TrackingSlackCli {
+    inner: Arc<dyn SlackCli>,
+}
+
+impl TrackingSlackCli {
+    pub fn new(inner: Arc<dyn SlackCli>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl SlackCli for TrackingSlackCli {
+    async fn send_msg_impl(&self, channel: &SlackChannelId, thread_ts: Option<&SlackTs>, msg: &str) -> Result<SlackTs> {
+        let invocation = match CURRENT_INVOCATION.try_with(|invocation| invocation.clone()) {
+            Ok(invocation) if invocation.is_same_thread(channel, thread_ts) => invocation,
+            _ => return self.inner.send_msg_impl(channel, thread_ts, msg).await,
+        };
+
+        let previous_reply = invocation.previous.lock().unwrap().pop_front();
+        let reply_ts = match previous_reply {
+            Some(reply_ts) => {
+                self.inner.update_msg(channel, &reply_ts, msg).await?;
+                reply_ts
+            }
+            None => self.inner.send_msg_impl(channel, thread_ts, msg).await?,
+        };
+        invocation.posted.lock().unwrap().push(reply_ts.clone());
+        Ok(reply_ts)
+    }
+
+    async fn send_blocks(
+        &self,
+        channel: &SlackChannelId,
+        thread_ts: Option<&SlackTs>,
+        text: &str,
+        blocks: Vec<SlackBlock>,
+    ) -> Result<SlackTs> {
+        self.inner.send_blocks(channel, thread_ts, text, blocks).await
+    }
+
+    async fn open_view(&self, trigger_id: &SlackTriggerId, view: SlackView) -> Result<SlackViewId> {
+        self.inner.open_view(trigger_id, view).await
+    }
+
+    async fn update_view(&self, view_id: &SlackViewId, view: SlackView) -> Result<()> {
+        self.inner.update_view(view_id, view).await
+    }
+
+    async fn update_msg(&self, channel: &SlackChannelId, msg_ts: &SlackTs, msg: &str) -> Result<()> {
+        self.inner.update_msg(channel, msg_ts, msg).await
+    }
+
+    async fn get_permalink(&self, channel: &SlackChannelId, msg_ts: &SlackTs) -> Result<String> {
+        self.inner.get_permalink(channel, msg_ts).await
+    }
+
+    async fn get_msgs_impl(
+        &self,
+        channel: &SlackChannelId,
+        msg_ts: &SlackTs,
+        limit: Option<u16>,
+    ) -> Result<Vec<SlackMsgHist>> {
+        self.inner.get_msgs_impl(channel, msg_ts, limit).await
+    }
+
+    async fn delete_msg(&self, channel: &SlackChannelId, msg_ts: &SlackTs) -> Result<()> {
+        self.inner.delete_msg(channel, msg_ts).await
+    }
+
+    async fn get_bot_info(&self) -> Result<SlackBotInfo> {
+        self.inner.get_bot_info().await
+    }
+
+    async fn get_team_id(&self) -> Result<SlackTeamId> {
+        self.inner.get_team_id().await
+    }
+
+    async fn get_user(&self, user: &SlackUserId) -> Result<SlackUser> {
+        self.inner.get_user(user).await
+    }
+
+    async fn download_file(&self, url: &str, max_size: usize) -> Result<Option<Vec<u8>>> {
+        self.inner.download_file(url, max_size).await
+    }
+
+    async fn get_known_channels(&self) -> Result<HashMap<SlackChannelId, String>> {
+        self.inner.get_known_channels().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::FakeSlackCli;
+
+    #[tokio::test]
+    async fn rerun_updates_previous_replies() {
+        let fake = Arc::new(FakeSlackCli::default());
+        let cli = TrackingSlackCli::new(fake.clone());
+        let tracker = ReplyTracker::new();
+        let (channel, thread_ts, msg_ts): (SlackChannelId, SlackTs, SlackTs) =
+            ("C1".into(), "1.0".into(), "1.1".into());
+
+        let first_run = async {
+            cli.send_reply(&channel, &thread_ts, "first").await.unwrap();
+            cli.send_reply(&channel, &thread_ts, "second").await.unwrap();
+        };
+        tracker.track(&channel, &thread_ts, &msg_ts, first_run).await;
+        assert_eq!(fake.sent_texts(), vec!["first", "second"]);
+
+        // the edited command replies once: the first reply is updated, the second one is not reused
+        let rerun = async { cli.send_reply(&channel, &thread_ts, "edited").await.unwrap() };
+        tracker.track(&channel, &thread_ts, &msg_ts, rerun).await;
+        assert_eq!(fake.sent_texts().len(), 2);
+        assert_eq!(*fake.updated.lock().unwrap(), vec![(channel.clone(), "2.1".into(), "edited".to_string())]);
+
+        // replies of the last run are tracked only, so extra replies are posted as new ones
+        let rerun = async {
+            cli.send_reply(&channel, &thread_ts, "edited again").await.unwrap();
+            cli.send_reply(&channel, &thread_ts, "more").await.unwrap();
+        };
+        tracker.track(&channel, &thread_ts, &msg_ts, rerun).await;
+        assert_eq!(fake.sent_texts(), vec!["first", "second", "more"]);
+        assert_eq!(fake.updated.lock().unwrap().last().unwrap().1, "2.1".into());
+    }
+
+    #[tokio::test]
+    async fn replies_to_other_threads_are_not_tracked() {
+        let fake = Arc::new(FakeSlackCli::default());
+        let cli = TrackingSlackCli::new(fake.clone());
+        let tracker = ReplyTracker::new();
+        let (channel, thread_ts, msg_ts): (SlackChannelId, SlackTs, SlackTs) =
+            ("C1".into(), "1.0".into(), "1.1".into());
+
+        for _ in 0..2 {
+            let run = async { cli.send_reply(&channel, &"9.0".into(), "elsewhere").await.unwrap() };
+            tracker.track(&channel, &thread_ts, &msg_ts, run).await;
+        }
+        assert_eq!(fake.sent_texts(), vec!["elsewhere", "elsewhere"]);
+        assert!(fake.updated.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn untracked_replies_are_posted() {
+        let fake = Arc::new(FakeSlackCli::default());
+        let cli = TrackingSlackCli::new(fake.clone());
+        cli.send_reply(&"C1".into(), &"1.0".into(), "plain").await.unwrap();
+        cli.send_reply(&"C1".into(), &"1.0".into(), "plain").await.unwrap();
+        assert_eq!(fake.sent_texts().len(), 2);
+    }
+}
@@ -0,0 +1,72 @@
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+
+pub(crate) const DEFAULT_ERROR_MESSAGE: &str = "Error occurred during handling. Check logs for details.";
+
+// What to reply when `help` or an unknown command is called
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HelpMode {
+    // built-in `help` command, also printed on unknown commands
+    #[default]
+    Full,
+    // built-in `help` command only, unknown commands are ignored
+    ExplicitOnly,
+    // no built-in help at all
+    Disabled,
+}
+
+// Logging setup done on serve
+#[derive(Debug, Clone, Default)]
+pub enum Logging {
+    // slack_morphism debug logs if `log` debug level is enabled
+    #[default]
+    Default,
+    // tracing subscriber with the given env filter, e.g. "slack_cmd_core=info,slack_morphism=warn"
+    EnvFilter(String),
+    // same as EnvFilter, but one json object per line
+    Json(String),
+    // logging is configured by the application
+    Disabled,
+}
+
+// Bot behaviour configured by SlackCmdBuilder
+#[derive(Debug, Clone)]
+pub(crate) struct Settings {
+    // re-run a command when its message is edited, updating the previous replies instead of posting new ones
+    pub rerun_on_edit: bool,
+    // alternative to the bot mention, e.g. `!` to call `!jira ...`
+    pub command_prefix: Option<String>,
+    pub help_mode: HelpMode,
+    // reply on handler failure
+    pub error_message: String,
+    // limit of commands handled at the same time, unlimited if None
+    pub max_concurrent_commands: Option<usize>,
+    // handler_name -> limit of its commands handled at the same time
+    pub handler_limits: HashMap<String, usize>,
+    pub logging: Logging,
+    // slack user ids allowed to use admin commands
+    pub admins: HashSet<String>,
+    // built-in command -> channel names it is available in, all channels if missing
+    pub builtin_channels: HashMap<String, HashSet<String>>,
+    // address of /metrics, /healthz and /readyz, disabled if None
+    pub metrics_addr: Option<SocketAddr>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            rerun_on_edit: false,
+            command_prefix: None,
+            help_mode: HelpMode::default(),
+            error_message: DEFAULT_ERROR_MESSAGE.to_string(),
+            max_concurrent_commands: None,
+            handler_limits: HashMap::new(),
+            logging: Logging::default(),
+            admins: HashSet::new(),
+            builtin_channels: HashMap::new(),
+            metrics_addr: None,
+        }
+    }
+}
@@ -4,20 +4,41 @@ pub use cli_impl::SlackCliImpl;
 use crate::SlackMsgHist;
 use anyhow::Result;
 use async_trait::async_trait;
-use slack_morphism::{SlackBotInfo, SlackChannelId, SlackTs};
+use slack_morphism::prelude::{SlackBlock, SlackView};
+use slack_morphism::{
+    SlackBotInfo, SlackChannelId, SlackTeamId, SlackTriggerId, SlackTs, SlackUser, SlackUserId, SlackViewId,
+};
 use std::collections::HashMap;
 
 #[async_trait]
 pub trait SlackCli: Send + Sync {
     async fn send_msg(&self, channel: &SlackChannelId, msg: &str) -> Result<()> {
-        self.send_msg_impl(channel, None, msg).await
+        self.send_msg_impl(channel, None, msg).await.map(|_| ())
     }
 
     async fn send_reply(&self, channel: &SlackChannelId, thread_ts: &SlackTs, msg: &str) -> Result<()> {
-        self.send_msg_impl(channel, Some(thread_ts), msg).await
+        self.send_msg_impl(channel, Some(thread_ts), msg).await.map(|_| ())
     }
 
-    async fn send_msg_impl(&self, _channel: &SlackChannelId, msg_ts: Option<&SlackTs>, msg: &str) -> Result<()>;
+    // returns ts of the posted message
+    async fn send_msg_impl(&self, _channel: &SlackChannelId, msg_ts: Option<&SlackTs>, msg: &str) -> Result<SlackTs>;
+
+    // Block Kit message, text is shown in notifications; returns ts of the posted message
+    async fn send_blocks(
+        &self,
+        channel: &SlackChannelId,
+        thread_ts: Option<&SlackTs>,
+        text: &str,
+        blocks: Vec<SlackBlock>,
+    ) -> Result<SlackTs>;
+
+    // trigger_id comes with interactions and is valid for 3 seconds, so slow content is better
+    // filled in later with update_view; returns id of the opened view
+    async fn open_view(&self, trigger_id: &SlackTriggerId, view: SlackView) -> Result<SlackViewId>;
+
+    async fn update_view(&self, view_id: &SlackViewId, view: SlackView) -> Result<()>;
+
+    async fn update_msg(&self, channel: &SlackChannelId, msg_ts: &SlackTs, msg: &str) -> Result<()>;
 
     async fn get_thread(&self, channel: &SlackChannelId, thread_ts: &SlackTs) -> Result<Vec<SlackMsgHist>> {
         Ok(self.get_msgs_impl(channel, thread_ts, None).await?)
@@ -50,5 +71,15 @@ pub trait SlackCli: Send + Sync {
             icons: None,
         })
     }
+    // workspaces are routed by the team id, so it must be unique per workspace
+    async fn get_team_id(&self) -> Result<SlackTeamId>;
+
+    // user profile with email requires `users:read.email` scope
+    async fn get_user(&self, user: &SlackUserId) -> Result<SlackUser>;
+
+    // private file content, e.g. `url_private_download` of a message file; None if larger than max_size.
+    // Urls of other hosts than slack files are rejected, so the token is not sent elsewhere
+    async fn download_file(&self, url: &str, max_size: usize) -> Result<Option<Vec<u8>>>;
+
     async fn get_known_channels(&self) -> Result<HashMap<SlackChannelId, String>>;
 }
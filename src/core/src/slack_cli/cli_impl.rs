@@ -1,3 +1,4 @@
+use crate::metrics::Metrics;
 use crate::slack_cli::SlackCli;
 use crate::SlackMsgHist;
 use anyhow::Result;
@@ -5,18 +6,29 @@ use anyhow::{anyhow, bail};
 use async_trait::async_trait;
 use slack_morphism::api::{
     SlackApiBotsInfoRequest, SlackApiChatDeleteRequest, SlackApiChatGetPermalinkRequest,
-    SlackApiChatPostMessageRequest, SlackApiConversationsListRequest, SlackApiConversationsRepliesRequest,
+    SlackApiChatPostMessageRequest, SlackApiChatUpdateRequest, SlackApiConversationsListRequest,
+    SlackApiConversationsRepliesRequest, SlackApiUsersInfoRequest, SlackApiViewsOpenRequest,
+    SlackApiViewsUpdateRequest,
 };
+use slack_morphism::errors::SlackClientError;
 use slack_morphism::hyper_tokio::{SlackClientHyperConnector, SlackClientHyperHttpsConnector};
+use slack_morphism::prelude::{SlackBlock, SlackView};
 use slack_morphism::{
     SlackApiToken, SlackBotInfo, SlackChannelId, SlackClient, SlackClientSession, SlackConversationType,
-    SlackMessageContent, SlackTs,
+    SlackMessageContent, SlackTeamId, SlackTriggerId, SlackTs, SlackUser, SlackUserId, SlackViewId,
 };
 use std::collections::HashMap;
+use std::sync::Arc;
+
+// hosts of `url_private` of slack files, the bot token is sent to them only
+const SLACK_FILE_HOSTS: [&str; 2] = ["files.slack.com", "files.slack-gov.com"];
 
 pub struct SlackCliImpl {
     token: SlackApiToken,
     client: SlackClient<SlackClientHyperHttpsConnector>,
+    // files are not available through slack api client
+    http: reqwest::Client,
+    metrics: Option<Arc<Metrics>>,
 }
 
 impl SlackCliImpl {
@@ -25,18 +37,33 @@ impl SlackCliImpl {
         Ok(Self {
             token: SlackApiToken::new(token.into()),
             client,
+            http: reqwest::Client::new(),
+            metrics: None,
         })
     }
 
+    pub(crate) fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
     // allow to get raw session for custom workflow
-    pub fn get_session(&self) -> SlackClientSession<SlackClientHyperHttpsConnector> {
+    pub fn get_session(&self) -> SlackClientSession<'_, SlackClientHyperHttpsConnector> {
         self.client.open_session(&self.token)
     }
+
+    fn observe<T>(&self, method: &str, result: Result<T, SlackClientError>) -> Result<T, SlackClientError> {
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_slack_call(method, &result);
+        }
+        result
+    }
 }
 
 #[async_trait]
 impl SlackCli for SlackCliImpl {
-    async fn send_msg_impl(&self, channel: &SlackChannelId, thread_ts: Option<&SlackTs>, msg: &str) -> Result<()> {
+    #[tracing::instrument(level = "debug", skip_all, fields(%channel))]
+    async fn send_msg_impl(&self, channel: &SlackChannelId, thread_ts: Option<&SlackTs>, msg: &str) -> Result<SlackTs> {
         log::trace!("send_msg_impl: channel_id='{channel}', thread_ts='{:?}', msg='{msg}'", thread_ts);
         let mut req = SlackApiChatPostMessageRequest::new(
             format!("{}", channel).into(),
@@ -45,8 +72,8 @@ impl SlackCli for SlackCliImpl {
         if let Some(thread_ts) = thread_ts {
             req = req.with_thread_ts(thread_ts.clone());
         }
-        match self.get_session().chat_post_message(&req).await {
-            Ok(_) => Ok(()),
+        match self.observe("chat.postMessage", self.get_session().chat_post_message(&req).await) {
+            Ok(rsp) => Ok(rsp.ts),
             Err(err) => {
                 tracing::log::error!("Fail to send msg='{msg}' to channel='{channel}', err='{:?}'", err);
                 Err(anyhow!(err))
@@ -54,9 +81,58 @@ impl SlackCli for SlackCliImpl {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(%channel))]
+    async fn send_blocks(
+        &self,
+        channel: &SlackChannelId,
+        thread_ts: Option<&SlackTs>,
+        text: &str,
+        blocks: Vec<SlackBlock>,
+    ) -> Result<SlackTs> {
+        let content = SlackMessageContent::new().with_text(text.into()).with_blocks(blocks);
+        let mut req = SlackApiChatPostMessageRequest::new(channel.clone(), content);
+        if let Some(thread_ts) = thread_ts {
+            req = req.with_thread_ts(thread_ts.clone());
+        }
+        let rsp = self.observe("chat.postMessage", self.get_session().chat_post_message(&req).await)?;
+        Ok(rsp.ts)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn open_view(&self, trigger_id: &SlackTriggerId, view: SlackView) -> Result<SlackViewId> {
+        let req = SlackApiViewsOpenRequest::new(trigger_id.clone(), view);
+        let resp = self.observe("views.open", self.get_session().views_open(&req).await)?;
+        Ok(resp.view.state_params.id)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(%view_id))]
+    async fn update_view(&self, view_id: &SlackViewId, view: SlackView) -> Result<()> {
+        let req = SlackApiViewsUpdateRequest::new(view).with_view_id(view_id.clone());
+        self.observe("views.update", self.get_session().views_update(&req).await)?;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(%channel, %msg_ts))]
+    async fn update_msg(&self, channel: &SlackChannelId, msg_ts: &SlackTs, msg: &str) -> Result<()> {
+        log::trace!("update_msg: channel_id='{channel}', msg_ts='{msg_ts}', msg='{msg}'");
+        let req = SlackApiChatUpdateRequest::new(
+            channel.clone(),
+            SlackMessageContent::new().with_text(msg.into()),
+            msg_ts.clone(),
+        );
+        match self.observe("chat.update", self.get_session().chat_update(&req).await) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                log::error!("Fail to update msg in channel='{channel}' with ts='{msg_ts}', err='{:?}'", err);
+                bail!(err)
+            }
+        }
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(%channel, %msg_ts))]
     async fn get_permalink(&self, channel: &SlackChannelId, msg_ts: &SlackTs) -> Result<String> {
         let req = SlackApiChatGetPermalinkRequest::new(channel.clone(), msg_ts.clone());
-        match self.get_session().chat_get_permalink(&req).await {
+        match self.observe("chat.getPermalink", self.get_session().chat_get_permalink(&req).await) {
             Ok(rsp) => Ok(rsp.permalink.to_string()),
             Err(err) => {
                 bail!(err)
@@ -64,6 +140,7 @@ impl SlackCli for SlackCliImpl {
         }
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(%channel, %ts))]
     async fn get_msgs_impl(
         &self,
         channel: &SlackChannelId,
@@ -84,12 +161,14 @@ impl SlackCli for SlackCliImpl {
             oldest: time_limits.clone(),
             inclusive: Some(true),
         };
-        Ok(self.get_session().conversations_replies(&req).await?.messages)
+        let rsp = self.observe("conversations.replies", self.get_session().conversations_replies(&req).await)?;
+        Ok(rsp.messages)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(%channel, %msg_ts))]
     async fn delete_msg(&self, channel: &SlackChannelId, msg_ts: &SlackTs) -> Result<()> {
         let req = SlackApiChatDeleteRequest::new(channel.clone(), msg_ts.clone());
-        match self.get_session().chat_delete(&req).await {
+        match self.observe("chat.delete", self.get_session().chat_delete(&req).await) {
             Ok(_) => Ok(()),
             Err(err) => {
                 log::warn!("Fail to delete msg from channel='{channel}' with ts='{msg_ts}, err='{:?}'", err);
@@ -100,9 +179,9 @@ impl SlackCli for SlackCliImpl {
 
     async fn get_bot_info(&self) -> Result<SlackBotInfo> {
         let session = self.get_session();
-        let auth_info = session.auth_test().await?;
-        let rsp =
-            session.bots_info(&SlackApiBotsInfoRequest::new().with_bot(auth_info.bot_id.unwrap().to_string())).await;
+        let auth_info = self.observe("auth.test", session.auth_test().await)?;
+        let req = SlackApiBotsInfoRequest::new().with_bot(auth_info.bot_id.unwrap().to_string());
+        let rsp = self.observe("bots.info", session.bots_info(&req).await);
         match rsp {
             Ok(rsp) => Ok(rsp.bot),
             Err(err) => {
@@ -112,6 +191,42 @@ impl SlackCli for SlackCliImpl {
         }
     }
 
+    async fn get_team_id(&self) -> Result<SlackTeamId> {
+        let auth_info = self.observe("auth.test", self.get_session().auth_test().await)?;
+        Ok(auth_info.team_id)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(%user))]
+    async fn get_user(&self, user: &SlackUserId) -> Result<SlackUser> {
+        let req = SlackApiUsersInfoRequest::new(user.clone());
+        let rsp = self.observe("users.info", self.get_session().users_info(&req).await)?;
+        Ok(rsp.user)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn download_file(&self, url: &str, max_size: usize) -> Result<Option<Vec<u8>>> {
+        if !is_slack_file_url(url) {
+            bail!("Fail to download file: {url} is not a slack file url");
+        }
+        let mut rsp = self.http.get(url).bearer_auth(&self.token.token_value.0).send().await?.error_for_status()?;
+        // slack returns login page instead of the file if the token has no `files:read` scope
+        let content_type = rsp.headers().get(reqwest::header::CONTENT_TYPE).and_then(|value| value.to_str().ok());
+        if content_type.is_some_and(|content_type| content_type.starts_with("text/html")) {
+            bail!("Fail to download file: access denied, `files:read` scope is required");
+        }
+        if rsp.content_length().is_some_and(|len| len as usize > max_size) {
+            return Ok(None);
+        }
+        let mut content = vec![];
+        while let Some(chunk) = rsp.chunk().await? {
+            if content.len() + chunk.len() > max_size {
+                return Ok(None);
+            }
+            content.extend_from_slice(&chunk);
+        }
+        Ok(Some(content))
+    }
+
     async fn get_known_channels(&self) -> Result<HashMap<SlackChannelId, String>> {
         let mut result = HashMap::new();
         let session = self.get_session();
@@ -122,7 +237,7 @@ impl SlackCli for SlackCliImpl {
             types: Some(vec![SlackConversationType::Public, SlackConversationType::Private]),
         };
         loop {
-            let rsp = session.conversations_list(&req).await?;
+            let rsp = self.observe("conversations.list", session.conversations_list(&req).await)?;
             for channel in rsp.channels {
                 result.insert(channel.id.clone(), channel.name.ok_or(anyhow!("Channel name is missing"))?);
             }
@@ -135,3 +250,37 @@ impl SlackCli for SlackCliImpl {
         Ok(result)
     }
 }
+
+fn is_slack_file_url(url: &str) -> bool {
+    match reqwest::Url::parse(url) {
+        Ok(url) => url.scheme() == "https" && url.host_str().is_some_and(|host| SLACK_FILE_HOSTS.contains(&host)),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_slack_file_urls() {
+        assert!(is_slack_file_url("https://files.slack.com/files-pri/T1-F1/download/report.pdf"));
+        assert!(is_slack_file_url("https://files.slack-gov.com/files-pri/T1-F1/report.pdf"));
+        assert!(is_slack_file_url("https://FILES.SLACK.COM/files-pri/T1-F1/report.pdf"));
+    }
+
+    #[test]
+    fn rejects_other_urls() {
+        for url in [
+            "http://files.slack.com/files-pri/T1-F1/report.pdf",
+            "https://files.slack.com.example.com/report.pdf",
+            "https://example.com/files.slack.com/report.pdf",
+            "https://files.slack.com@example.com/report.pdf",
+            "https://slack.com/files-pri/T1-F1/report.pdf",
+            "files.slack.com/files-pri/T1-F1/report.pdf",
+            "",
+        ] {
+            assert!(!is_slack_file_url(url), "{url}");
+        }
+    }
+}
@@ -1,13 +1,29 @@
+use crate::audit::ArcAuditSink;
+use crate::dedup::{EventDedup, DEFAULT_DEDUP_CAPACITY, DEFAULT_DEDUP_WINDOW};
 use crate::default_help_handler::DefaultHelpHandler;
 use crate::handler::{ArcMsgHandler, ALL_CHANNELS_MARKER};
+use crate::health::Health;
+use crate::listener::run_handler;
+use crate::metrics::Metrics;
+use crate::middleware::ArcMiddleware;
+use crate::queue::{CommandQueue, GLOBAL_QUEUE};
+use crate::reload::ConfigReloader;
+use crate::replies::{ReplyTracker, TrackingSlackCli};
+use crate::settings::Settings;
 use crate::slack_cli::SlackCli;
+use crate::storage::{ArcStorage, InMemoryStorage};
+use crate::utils::extract_channel_thread;
+use crate::SlackMsgEv;
 use anyhow::{bail, Result};
+use arc_swap::ArcSwap;
 use dashmap::DashMap;
-use slack_morphism::{SlackBotInfo, SlackChannelId};
+use slack_morphism::{SlackBotInfo, SlackChannelId, SlackTeamId};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+// State of a single workspace. Workspaces of one bot share handlers, settings, storage and limits
 pub struct BotState {
+    pub team_id: SlackTeamId,
     pub bot_marker: String,
     pub bot_info: SlackBotInfo,
     pub slack_cli: Arc<dyn SlackCli>,
@@ -15,18 +31,38 @@ pub struct BotState {
     pub known_channels: DashMap<SlackChannelId, String>,
     pub known_channels_rev: DashMap<String, SlackChannelId>,
     pub start_time: std::time::Instant,
-    pub(crate) help_handler: DefaultHelpHandler,
-    handlers_index: HandlerIndex,
+    // persistent handlers data, in-memory by default
+    pub storage: ArcStorage,
+    pub(crate) dedup: Arc<EventDedup>,
+    pub(crate) replies: ReplyTracker,
+    pub(crate) settings: Settings,
+    pub(crate) middlewares: Vec<ArcMiddleware>,
+    pub(crate) command_queue: Option<Arc<CommandQueue>>,
+    pub(crate) reloader: Option<Arc<ConfigReloader>>,
+    pub(crate) metrics: Arc<Metrics>,
+    pub(crate) health: Arc<Health>,
+    pub(crate) audit_sinks: Vec<ArcAuditSink>,
+    handlers: Arc<ArcSwap<Handlers>>,
 }
 
-impl BotState {
-    pub(crate) async fn new<I>(slack_cli: Arc<dyn SlackCli>, handlers: I) -> Result<Self>
-    where
-        I: IntoIterator<Item = ArcMsgHandler>,
-    {
+// per-workspace part of BotState
+struct Workspace {
+    team_id: SlackTeamId,
+    bot_marker: String,
+    bot_info: SlackBotInfo,
+    slack_cli: Arc<dyn SlackCli>,
+    known_channels: DashMap<SlackChannelId, String>,
+    known_channels_rev: DashMap<String, SlackChannelId>,
+}
+
+impl Workspace {
+    async fn load(slack_cli: Arc<dyn SlackCli>, settings: &Settings, health: &Health) -> Result<Self> {
+        let team_id = slack_cli.get_team_id().await?;
         let bot_info = slack_cli.get_bot_info().await?;
+        health.set_bot_info_loaded();
 
         let known_channels = slack_cli.get_known_channels().await?.into_iter().collect::<DashMap<_, _>>();
+        health.set_channels_fetched();
         let known_channels_rev = known_channels.iter().map(|item| (item.value().clone(), item.key().clone())).collect();
 
         let bot_marker = match &bot_info.user_id {
@@ -34,23 +70,106 @@ impl BotState {
             None => bail!("Bot user_id is empty"),
         };
 
-        let handlers = handlers.into_iter().collect::<Vec<_>>();
-        let help_handler = DefaultHelpHandler::new(&handlers);
-        let handlers_index = HandlerIndex::new(handlers);
-
-        let state = Self {
+        let slack_cli: Arc<dyn SlackCli> = match settings.rerun_on_edit {
+            true => Arc::new(TrackingSlackCli::new(slack_cli)),
+            false => slack_cli,
+        };
+        Ok(Self {
+            team_id,
             bot_marker,
             bot_info,
             slack_cli,
             known_channels,
             known_channels_rev,
+        })
+    }
+}
+
+// swapped as a whole on reload
+pub(crate) struct Handlers {
+    pub index: HandlerIndex,
+    pub help: DefaultHelpHandler,
+    // handler_name -> queue of its commands
+    pub queues: HashMap<String, CommandQueue>,
+}
+
+impl Handlers {
+    fn new(handlers: Vec<ArcMsgHandler>, limits: &HashMap<String, usize>) -> Self {
+        Self {
+            help: DefaultHelpHandler::new(&handlers),
+            index: HandlerIndex::new(handlers),
+            queues: limits.iter().map(|(name, limit)| (name.clone(), CommandQueue::new(name, *limit))).collect(),
+        }
+    }
+}
+
+impl BotState {
+    pub(crate) async fn new<I>(
+        slack_cli: Arc<dyn SlackCli>,
+        handlers: I,
+        middlewares: Vec<ArcMiddleware>,
+        settings: Settings,
+        reloader: Option<ConfigReloader>,
+        metrics: Arc<Metrics>,
+        health: Arc<Health>,
+    ) -> Result<Self>
+    where
+        I: IntoIterator<Item = ArcMsgHandler>,
+    {
+        let workspace = Workspace::load(slack_cli, &settings, &health).await?;
+        let handlers = Handlers::new(handlers.into_iter().collect(), &settings.handler_limits);
+
+        let state = Self {
+            team_id: workspace.team_id,
+            bot_marker: workspace.bot_marker,
+            bot_info: workspace.bot_info,
+            slack_cli: workspace.slack_cli,
+            known_channels: workspace.known_channels,
+            known_channels_rev: workspace.known_channels_rev,
             start_time: std::time::Instant::now(),
-            help_handler,
-            handlers_index,
+            storage: Arc::new(InMemoryStorage::new()),
+            dedup: Arc::new(EventDedup::new(DEFAULT_DEDUP_WINDOW, DEFAULT_DEDUP_CAPACITY)),
+            replies: ReplyTracker::new(),
+            command_queue: settings
+                .max_concurrent_commands
+                .map(|limit| Arc::new(CommandQueue::new(GLOBAL_QUEUE, limit))),
+            settings,
+            middlewares,
+            reloader: reloader.map(Arc::new),
+            metrics,
+            health,
+            audit_sinks: Vec::new(),
+            handlers: Arc::new(ArcSwap::from_pointee(handlers)),
         };
         Ok(state)
     }
 
+    // state of another workspace of the same bot, with its own readiness
+    pub(crate) async fn for_workspace(&self, slack_cli: Arc<dyn SlackCli>) -> Result<Self> {
+        let health = Arc::new(Health::default());
+        let workspace = Workspace::load(slack_cli, &self.settings, &health).await?;
+        Ok(Self {
+            team_id: workspace.team_id,
+            bot_marker: workspace.bot_marker,
+            bot_info: workspace.bot_info,
+            slack_cli: workspace.slack_cli,
+            known_channels: workspace.known_channels,
+            known_channels_rev: workspace.known_channels_rev,
+            start_time: self.start_time,
+            storage: self.storage.clone(),
+            dedup: self.dedup.clone(),
+            replies: ReplyTracker::new(),
+            settings: self.settings.clone(),
+            middlewares: self.middlewares.clone(),
+            command_queue: self.command_queue.clone(),
+            reloader: self.reloader.clone(),
+            metrics: self.metrics.clone(),
+            health,
+            audit_sinks: self.audit_sinks.clone(),
+            handlers: self.handlers.clone(),
+        })
+    }
+
     pub(crate) fn get_msg_handler(&self, channel_id: &SlackChannelId, handler_name: &str) -> Option<ArcMsgHandler> {
         let channel_name = match self.known_channels.get(channel_id) {
             Some(name) => name.value().clone(),
@@ -59,11 +178,75 @@ impl BotState {
                 return None;
             }
         };
-        self.handlers_index.get(&channel_name, handler_name)
+        self.handlers.load().index.get(&channel_name, handler_name)
+    }
+
+    // built-in commands are limited to the channels from settings, like handlers
+    pub(crate) fn is_builtin_available(&self, channel_id: &SlackChannelId, command: &str) -> bool {
+        let channels = match self.settings.builtin_channels.get(command) {
+            Some(channels) => channels,
+            None => return true,
+        };
+        channels.contains(ALL_CHANNELS_MARKER)
+            || self.known_channels.get(channel_id).is_some_and(|name| channels.contains(name.value()))
+    }
+
+    // handlers of the channel interested in plain messages
+    pub(crate) fn get_message_handlers(&self, channel_id: &SlackChannelId) -> Vec<ArcMsgHandler> {
+        match self.known_channels.get(channel_id) {
+            Some(name) => self.handlers.load().index.with_message_pattern(name.value()),
+            None => vec![],
+        }
+    }
+
+    // handler of any channel, e.g. for webhooks
+    pub(crate) fn get_handler_by_name(&self, handler_name: &str) -> Option<ArcMsgHandler> {
+        self.handlers.load().index.by_name(handler_name)
+    }
+
+    pub(crate) fn handlers(&self) -> Arc<Handlers> {
+        self.handlers.load_full()
+    }
+
+    // in-flight commands keep permits of the previous queues, so limits may be exceeded during reload
+    pub(crate) fn set_handlers(&self, handlers: Vec<ArcMsgHandler>, limits: &HashMap<String, usize>) {
+        self.handlers.store(Arc::new(Handlers::new(handlers, limits)));
+    }
+
+    // runs the command as if it was posted by the sender of msg_ev, e.g. one built from a submitted modal.
+    // Handler failures are replied to the thread, Err only means the command was not started
+    pub async fn run_command(&self, args: &[String], msg_ev: &SlackMsgEv) -> Result<()> {
+        let (channel_id, thread_ts) = extract_channel_thread(msg_ev)?;
+        let handler_name = args.first().map(String::as_str).unwrap_or_default();
+        let handler = match self.get_msg_handler(&channel_id, handler_name) {
+            Some(handler) => handler,
+            None => bail!("Handler '{handler_name}' is not available in channel '{channel_id}'"),
+        };
+        run_handler(handler_name, Some(handler), args, msg_ev, self, &channel_id, &thread_ts).await;
+        Ok(())
+    }
+
+    // returns command text if the message is addressed to the bot
+    pub(crate) fn strip_command_marker<'a>(&self, msg_body: &'a str) -> Option<&'a str> {
+        if let Some(body) = msg_body.strip_prefix(&self.bot_marker) {
+            return Some(body.trim());
+        }
+        let prefix = self.settings.command_prefix.as_ref()?;
+        msg_body.strip_prefix(prefix.as_str()).map(str::trim)
+    }
+
+    // slack connection is alive and the bot is ready to handle commands of this workspace
+    pub fn is_ready(&self) -> bool {
+        self.health.is_ready()
+    }
+
+    // number of redelivered events dropped by deduplication
+    pub fn dropped_duplicates(&self) -> u64 {
+        self.dedup.dropped()
     }
 }
 
-struct HandlerIndex {
+pub(crate) struct HandlerIndex {
     // channel_name -> handler_name -> handler
     channel_index: HashMap<String, HashMap<String, ArcMsgHandler>>,
     all_channels: HashMap<String, ArcMsgHandler>,
@@ -102,4 +285,17 @@ impl HandlerIndex {
         }
         self.all_channels.get(handler_name).cloned()
     }
+
+    fn by_name(&self, handler_name: &str) -> Option<ArcMsgHandler> {
+        let mut handlers = self.all_channels.values().chain(self.channel_index.values().flat_map(HashMap::values));
+        handlers.find(|handler| handler.name() == handler_name).cloned()
+    }
+
+    fn with_message_pattern(&self, channel_name: &str) -> Vec<ArcMsgHandler> {
+        let handlers = match self.channel_index.get(channel_name) {
+            Some(handlers) => handlers,
+            None => &self.all_channels,
+        };
+        handlers.values().filter(|handler| handler.message_pattern().is_some()).cloned().collect()
+    }
 }
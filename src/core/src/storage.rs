@@ -0,0 +1,177 @@
+mod memory;
+mod sled_backend;
+
+pub use memory::InMemoryStorage;
+pub use sled_backend::SledStorage;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Key-value storage available to handlers as `BotState::storage`.
+// Namespaces separate handlers data, e.g. "jira" or "jira:C0123" for per-channel data.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<String>>;
+
+    // value expires after ttl, kept forever if None
+    async fn set(&self, namespace: &str, key: &str, value: &str, ttl: Option<Duration>) -> Result<()>;
+
+    // returns true if the key existed
+    async fn delete(&self, namespace: &str, key: &str) -> Result<bool>;
+
+    // key-value pairs with keys starting with prefix, sorted by key
+    async fn list(&self, namespace: &str, prefix: &str) -> Result<Vec<(String, String)>>;
+}
+pub type ArcStorage = Arc<dyn Storage>;
+
+impl dyn Storage {
+    pub async fn get_json<T: DeserializeOwned>(&self, namespace: &str, key: &str) -> Result<Option<T>> {
+        match self.get(namespace, key).await? {
+            Some(value) => Ok(Some(serde_json::from_str(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn set_json<T: Serialize + Sync>(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: &T,
+        ttl: Option<Duration>,
+    ) -> Result<()> {
+        self.set(namespace, key, &serde_json::to_string(value)?, ttl).await
+    }
+}
+
+// stored value with its expiration time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    value: String,
+    // unix time in milliseconds
+    expires_at: Option<u64>,
+}
+
+impl Entry {
+    fn new(value: &str, ttl: Option<Duration>) -> Self {
+        Self {
+            value: value.into(),
+            expires_at: ttl.map(|ttl| now_millis() + ttl.as_millis() as u64),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now_millis())
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    // every backend, the directory keeps sled files until the test ends
+    fn backends() -> (tempfile::TempDir, Vec<Box<dyn Storage>>) {
+        let dir = tempfile::tempdir().unwrap();
+        let sled = SledStorage::open(dir.path()).unwrap();
+        (dir, vec![Box::new(InMemoryStorage::new()), Box::new(sled)])
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let (_dir, backends) = backends();
+        for storage in &backends {
+            assert_eq!(storage.get("ns", "key").await.unwrap(), None);
+            storage.set("ns", "key", "value", None).await.unwrap();
+            assert_eq!(storage.get("ns", "key").await.unwrap(), Some("value".into()));
+            storage.set("ns", "key", "updated", None).await.unwrap();
+            assert_eq!(storage.get("ns", "key").await.unwrap(), Some("updated".into()));
+
+            assert!(storage.delete("ns", "key").await.unwrap());
+            assert!(!storage.delete("ns", "key").await.unwrap());
+            assert_eq!(storage.get("ns", "key").await.unwrap(), None);
+        }
+    }
+
+    #[tokio::test]
+    async fn expires_after_ttl() {
+        let (_dir, backends) = backends();
+        for storage in &backends {
+            storage.set("ns", "short", "value", Some(Duration::from_millis(20))).await.unwrap();
+            storage.set("ns", "long", "value", Some(Duration::from_secs(60))).await.unwrap();
+            assert_eq!(storage.get("ns", "short").await.unwrap(), Some("value".into()));
+
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            assert_eq!(storage.get("ns", "short").await.unwrap(), None);
+            assert_eq!(storage.get("ns", "long").await.unwrap(), Some("value".into()));
+
+            storage.set("ns", "short", "value", Some(Duration::from_millis(20))).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            assert_eq!(storage.list("ns", "").await.unwrap(), vec![("long".into(), "value".into())]);
+            assert!(!storage.delete("ns", "short").await.unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn isolates_namespaces() {
+        let (_dir, backends) = backends();
+        for storage in &backends {
+            storage.set("jira", "key", "jira value", None).await.unwrap();
+            storage.set("jira:C1", "key", "channel value", None).await.unwrap();
+            assert_eq!(storage.get("jira", "key").await.unwrap(), Some("jira value".into()));
+            assert_eq!(storage.get("jira:C1", "key").await.unwrap(), Some("channel value".into()));
+            assert_eq!(storage.get("other", "key").await.unwrap(), None);
+
+            assert!(storage.delete("jira", "key").await.unwrap());
+            assert_eq!(storage.get("jira:C1", "key").await.unwrap(), Some("channel value".into()));
+            assert_eq!(storage.list("jira", "").await.unwrap(), vec![]);
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_prefixed_keys_sorted() {
+        let (_dir, backends) = backends();
+        for storage in &backends {
+            for key in ["user:b", "other", "user:c", "user:a", "use"] {
+                storage.set("ns", key, &key.to_uppercase(), None).await.unwrap();
+            }
+            storage.set("other_ns", "user:d", "D", None).await.unwrap();
+
+            let listed = storage.list("ns", "user:").await.unwrap();
+            let expected = [("user:a", "USER:A"), ("user:b", "USER:B"), ("user:c", "USER:C")];
+            assert_eq!(listed, expected.map(|(key, value)| (key.to_string(), value.to_string())));
+            assert_eq!(storage.list("ns", "").await.unwrap().len(), 5);
+            assert_eq!(storage.list("ns", "missing").await.unwrap(), vec![]);
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Project {
+        key: String,
+        ids: Vec<u32>,
+    }
+
+    #[tokio::test]
+    async fn stores_json() {
+        let (_dir, backends) = backends();
+        for storage in &backends {
+            let project = Project {
+                key: "PRJ".into(),
+                ids: vec![1, 2],
+            };
+            storage.set_json("ns", "project", &project, None).await.unwrap();
+            assert_eq!(storage.get_json::<Project>("ns", "project").await.unwrap(), Some(project));
+            assert_eq!(storage.get_json::<Project>("ns", "missing").await.unwrap(), None);
+
+            storage.set("ns", "broken", "not json", None).await.unwrap();
+            assert!(storage.get_json::<Project>("ns", "broken").await.is_err());
+        }
+    }
+}
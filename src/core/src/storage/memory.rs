@@ -0,0 +1,56 @@
+use crate::storage::{Entry, Storage};
+use anyhow::Result;
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::time::Duration;
+
+// Default storage, data is lost on restart
+#[derive(Default)]
+pub struct InMemoryStorage {
+    // (namespace, key) -> entry
+    entries: DashMap<(String, String), Entry>,
+}
+
+impl InMemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Storage for InMemoryStorage {
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<String>> {
+        let id = (namespace.to_string(), key.to_string());
+        let expired = match self.entries.get(&id) {
+            Some(entry) if !entry.is_expired() => return Ok(Some(entry.value.clone())),
+            Some(_) => true,
+            None => false,
+        };
+        if expired {
+            self.entries.remove(&id);
+        }
+        Ok(None)
+    }
+
+    async fn set(&self, namespace: &str, key: &str, value: &str, ttl: Option<Duration>) -> Result<()> {
+        self.entries.insert((namespace.into(), key.into()), Entry::new(value, ttl));
+        Ok(())
+    }
+
+    async fn delete(&self, namespace: &str, key: &str) -> Result<bool> {
+        let removed = self.entries.remove(&(namespace.to_string(), key.to_string()));
+        Ok(removed.is_some_and(|(_, entry)| !entry.is_expired()))
+    }
+
+    async fn list(&self, namespace: &str, prefix: &str) -> Result<Vec<(String, String)>> {
+        self.entries.retain(|_, entry| !entry.is_expired());
+        let mut result = self
+            .entries
+            .iter()
+            .filter(|item| item.key().0 == namespace && item.key().1.starts_with(prefix))
+            .map(|item| (item.key().1.clone(), item.value().value.clone()))
+            .collect::<Vec<_>>();
+        result.sort();
+        Ok(result)
+    }
+}
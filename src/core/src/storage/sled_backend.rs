@@ -0,0 +1,71 @@
+use crate::storage::{Entry, Storage};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::path::Path;
+use std::time::Duration;
+
+// File-backed storage, one sled tree per namespace.
+// Expired values are removed on access.
+pub struct SledStorage {
+    db: sled::Db,
+}
+
+impl SledStorage {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let db = sled::open(path).with_context(|| format!("Fail to open storage {}", path.display()))?;
+        Ok(Self { db })
+    }
+
+    fn decode(raw: &[u8]) -> Result<Entry> {
+        Ok(serde_json::from_slice(raw)?)
+    }
+}
+
+#[async_trait]
+impl Storage for SledStorage {
+    async fn get(&self, namespace: &str, key: &str) -> Result<Option<String>> {
+        let tree = self.db.open_tree(namespace)?;
+        let entry = match tree.get(key)? {
+            Some(raw) => Self::decode(&raw)?,
+            None => return Ok(None),
+        };
+        if entry.is_expired() {
+            tree.remove(key)?;
+            return Ok(None);
+        }
+        Ok(Some(entry.value))
+    }
+
+    async fn set(&self, namespace: &str, key: &str, value: &str, ttl: Option<Duration>) -> Result<()> {
+        let tree = self.db.open_tree(namespace)?;
+        tree.insert(key, serde_json::to_vec(&Entry::new(value, ttl))?)?;
+        tree.flush_async().await?;
+        Ok(())
+    }
+
+    async fn delete(&self, namespace: &str, key: &str) -> Result<bool> {
+        let tree = self.db.open_tree(namespace)?;
+        let removed = match tree.remove(key)? {
+            Some(raw) => !Self::decode(&raw)?.is_expired(),
+            None => false,
+        };
+        tree.flush_async().await?;
+        Ok(removed)
+    }
+
+    async fn list(&self, namespace: &str, prefix: &str) -> Result<Vec<(String, String)>> {
+        let tree = self.db.open_tree(namespace)?;
+        let mut result = vec![];
+        for item in tree.scan_prefix(prefix) {
+            let (key, raw) = item?;
+            let entry = Self::decode(&raw)?;
+            if entry.is_expired() {
+                tree.remove(&key)?;
+                continue;
+            }
+            result.push((String::from_utf8(key.to_vec())?, entry.value));
+        }
+        Ok(result)
+    }
+}
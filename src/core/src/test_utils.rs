@@ -0,0 +1,85 @@
+use crate::slack_cli::SlackCli;
+use crate::SlackMsgHist;
+use anyhow::Result;
+use async_trait::async_trait;
+use slack_morphism::prelude::{SlackBlock, SlackView};
+use slack_morphism::{SlackChannelId, SlackTeamId, SlackTriggerId, SlackTs, SlackUser, SlackUserId, SlackViewId};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+// SlackCli recording posted and updated messages
+#[derive(Default)]
+pub(crate) struct FakeSlackCli {
+    // channel, thread_ts, text
+    pub sent: Mutex<Vec<(SlackChannelId, Option<SlackTs>, String)>>,
+    // channel, msg_ts, text
+    pub updated: Mutex<Vec<(SlackChannelId, SlackTs, String)>>,
+    last_ts: AtomicU64,
+}
+
+impl FakeSlackCli {
+    pub fn sent_texts(&self) -> Vec<String> {
+        self.sent.lock().unwrap().iter().map(|(_, _, text)| text.clone()).collect()
+    }
+}
+
+#[async_trait]
+impl SlackCli for FakeSlackCli {
+    async fn send_msg_impl(&self, channel: &SlackChannelId, thread_ts: Option<&SlackTs>, msg: &str) -> Result<SlackTs> {
+        self.sent.lock().unwrap().push((channel.clone(), thread_ts.cloned(), msg.to_string()));
+        Ok(format!("2.{}", self.last_ts.fetch_add(1, Ordering::Relaxed) + 1).into())
+    }
+
+    async fn send_blocks(
+        &self,
+        channel: &SlackChannelId,
+        thread_ts: Option<&SlackTs>,
+        text: &str,
+        _blocks: Vec<SlackBlock>,
+    ) -> Result<SlackTs> {
+        self.send_msg_impl(channel, thread_ts, text).await
+    }
+
+    async fn open_view(&self, _trigger_id: &SlackTriggerId, _view: SlackView) -> Result<SlackViewId> {
+        unimplemented!()
+    }
+
+    async fn update_view(&self, _view_id: &SlackViewId, _view: SlackView) -> Result<()> {
+        unimplemented!()
+    }
+
+    async fn update_msg(&self, channel: &SlackChannelId, msg_ts: &SlackTs, msg: &str) -> Result<()> {
+        self.updated.lock().unwrap().push((channel.clone(), msg_ts.clone(), msg.to_string()));
+        Ok(())
+    }
+
+    async fn get_permalink(&self, channel: &SlackChannelId, msg_ts: &SlackTs) -> Result<String> {
+        Ok(format!("https://slack.test/{channel}/{msg_ts}"))
+    }
+
+    async fn get_msgs_impl(
+        &self,
+        _channel: &SlackChannelId,
+        _msg_ts: &SlackTs,
+        _limit: Option<u16>,
+    ) -> Result<Vec<SlackMsgHist>> {
+        Ok(vec![])
+    }
+
+    async fn get_team_id(&self) -> Result<SlackTeamId> {
+        Ok("T1".into())
+    }
+
+    async fn get_user(&self, _user: &SlackUserId) -> Result<SlackUser> {
+        unimplemented!()
+    }
+
+    async fn download_file(&self, _url: &str, _max_size: usize) -> Result<Option<Vec<u8>>> {
+        unimplemented!()
+    }
+
+    async fn get_known_channels(&self) -> Result<HashMap<SlackChannelId, String>> {
+        Ok(HashMap::from([("C1".into(), "general".to_string())]))
+    }
+}
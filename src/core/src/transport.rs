@@ -0,0 +1,31 @@
+use std::net::SocketAddr;
+
+// How the bot receives events from slack
+#[derive(Debug, Clone)]
+pub enum Transport {
+    // outbound websocket connection, requires app-level token
+    SocketMode {
+        socket_token: String,
+    },
+    // inbound Events API requests, verified with the app signing secret.
+    // Routes: /slack/events, /slack/commands, /slack/interactions
+    Http {
+        bind_addr: SocketAddr,
+        signing_secret: String,
+    },
+}
+
+impl Transport {
+    pub fn socket_mode(socket_token: &str) -> Self {
+        Self::SocketMode {
+            socket_token: socket_token.into(),
+        }
+    }
+
+    pub fn http(bind_addr: SocketAddr, signing_secret: &str) -> Self {
+        Self::Http {
+            bind_addr,
+            signing_secret: signing_secret.into(),
+        }
+    }
+}
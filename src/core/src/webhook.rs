@@ -0,0 +1,12 @@
+use axum::body::Bytes;
+use axum::http::HeaderMap;
+use std::collections::HashMap;
+
+// Request to `POST /hooks/{handler}` of the ops server, e.g. event of an external service.
+// Passed to `MsgHandler::handle_webhook` of the handler with that name.
+#[derive(Debug, Clone)]
+pub struct WebhookRequest {
+    pub headers: HeaderMap,
+    pub query: HashMap<String, String>,
+    pub body: Bytes,
+}
@@ -0,0 +1,50 @@
+use crate::state::BotState;
+use slack_morphism::SlackTeamId;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Routes events to the state of the workspace they came from
+pub struct Workspaces {
+    primary: Arc<BotState>,
+    by_team: HashMap<SlackTeamId, Arc<BotState>>,
+}
+
+impl Workspaces {
+    pub(crate) fn new(primary: Arc<BotState>, others: Vec<Arc<BotState>>) -> Self {
+        let mut by_team = HashMap::new();
+        for state in std::iter::once(primary.clone()).chain(others) {
+            log::info!("serving workspace team_id='{}', bot='{}'", state.team_id, state.bot_info.name);
+            if by_team.insert(state.team_id.clone(), state.clone()).is_some() {
+                log::warn!("workspace team_id='{}' is configured twice", state.team_id);
+            }
+        }
+        Self { primary, by_team }
+    }
+
+    pub fn primary(&self) -> &Arc<BotState> {
+        &self.primary
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = &Arc<BotState>> {
+        self.by_team.values()
+    }
+
+    // events without team use the primary workspace. Unknown teams, e.g. other workspaces of Enterprise Grid
+    // with org-wide install, are not served: the bot has no token to reply there
+    pub fn get(&self, team_id: Option<&SlackTeamId>) -> Option<Arc<BotState>> {
+        match team_id {
+            Some(team_id) => self.by_team.get(team_id).cloned(),
+            None => Some(self.primary.clone()),
+        }
+    }
+
+    // names of the checks which are not passed yet, by workspace
+    pub(crate) fn pending(&self) -> Vec<String> {
+        let mut pending = vec![];
+        for state in self.by_team.values() {
+            pending.extend(state.health.pending().into_iter().map(|check| format!("{check} ({})", state.team_id)));
+        }
+        pending.sort();
+        pending
+    }
+}
@@ -0,0 +1,16 @@
+use slack_cmd_core::{HandlerRegistry, SlackCmdBuilder};
+use slack_cmd_handlers::register_handlers;
+use std::env;
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let config_path = env::args().nth(1).unwrap_or("src/examples/config.toml".to_string());
+
+    let mut registry = HandlerRegistry::new();
+    register_handlers(&mut registry);
+
+    // handlers are reloaded on SIGHUP or `reload` command from admins
+    SlackCmdBuilder::from_config_file(config_path, Arc::new(registry))?.build()?.serve().await
+}
@@ -1,5 +1,5 @@
 use slack_cmd_core::ALL_CHANNELS;
-use slack_cmd_handlers::{InfoHandler, JiraHandler};
+use slack_cmd_handlers::{InfoHandler, JiraHandler, JiraHandlerOptions};
 use std::env;
 
 #[tokio::main]
@@ -14,7 +14,13 @@ async fn main() -> anyhow::Result<()> {
 
     let handlers = [
         InfoHandler::make(),
-        JiraHandler::make(&jira_url, &jira_user_email, &jira_token, ALL_CHANNELS.iter().cloned()),
+        JiraHandler::make(
+            &jira_url,
+            &jira_user_email,
+            &jira_token,
+            ALL_CHANNELS.iter().cloned(),
+            JiraHandlerOptions::default(),
+        ),
     ];
 
     slack_cmd_core::run(&oauth_token, &socket_token, handlers).await?;
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use slack_cmd_core::utils::extract_channel_thread;
-use slack_cmd_core::{ArcMsgHandler, BotState, MsgHandler, SlackMsgEv, ALL_CHANNELS};
+use slack_cmd_core::{ArcMsgHandler, BotState, HandlerConfig, MsgHandler, SlackMsgEv, ALL_CHANNELS};
 use std::collections::HashSet;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -36,10 +36,12 @@ impl MsgHandler for InfoHandler {
         • Bot user_id: `{}`\n\
         • Bot user_name: `{}`\n\
         • Uptime: {:?}\n\
+        • Dropped duplicate events: {}\n\
         • Known channels:\n{}",
             bot_state.bot_info.user_id.as_ref().unwrap_or(&"N/A".to_string()),
             bot_state.bot_info.name,
             uptime,
+            bot_state.dropped_duplicates(),
             known_channels,
         );
         bot_state.slack_cli.send_reply(&channel, &thread, &response).await
@@ -50,4 +52,9 @@ impl InfoHandler {
     pub fn make() -> ArcMsgHandler {
         Arc::new(Self {})
     }
+
+    // no params, always available in all channels
+    pub fn from_config(_: &HandlerConfig) -> Result<ArcMsgHandler> {
+        Ok(Self::make())
+    }
 }
@@ -1,30 +1,172 @@
-use anyhow::{bail, Result};
+mod adf;
+mod attachments;
+mod client;
+mod fields;
+mod modal;
+mod mrkdwn;
+mod search;
+mod sync;
+mod transitions;
+mod unfurl;
+mod users;
+
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use clap::Parser;
+use attachments::AttachmentLimits;
+use clap::{Args, Parser, Subcommand};
+use client::JiraClient;
+pub use fields::JiraProjectDefaults;
+use regex::Regex;
+use search::SearchPage;
+use serde::Deserialize;
 use serde_json::{json, Value};
-use slack_cmd_core::utils::{extract_channel_thread, extract_msg_body};
-use slack_cmd_core::{ArcMsgHandler, BotState, MsgHandler, SlackMsgEv};
-use std::cmp::min;
-use std::collections::HashSet;
-use std::sync::Arc;
+use slack_cmd_core::utils::{extract_channel_thread, extract_msg_body, extract_msg_ts};
+use slack_cmd_core::{
+    ArcMsgHandler, BotState, HandlerConfig, HandlerError, MsgHandler, SlackMsgEv, WebhookRequest, Workspaces,
+};
+use slack_morphism::prelude::{
+    SlackInteractionBlockActionsEvent, SlackInteractionEvent, SlackInteractionViewSubmissionEvent, SlackView,
+};
+use slack_morphism::{SlackFile, SlackMessageOrigin, SlackMessageSender};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use users::UserMapping;
+
+// channel setting with the default project key
+const PROJECT_SETTING: &str = "jira.project";
+// thread search state for `jira search more`
+const SEARCH_NAMESPACE: &str = "jira:search";
+const SEARCH_TTL: Duration = Duration::from_secs(3600);
 
 #[derive(Debug, Parser, Clone)]
-#[command(name = "jira", about = "Create jira ticket")]
+#[command(
+    name = "jira",
+    about = "Create jira ticket, or work with existing ones",
+    args_conflicts_with_subcommands = true
+)]
 struct JiraHandlerArgs {
+    #[command(subcommand)]
+    command: Option<JiraCommand>,
+    #[command(flatten)]
+    create: CreateArgs,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+enum JiraCommand {
+    #[command(about = "Comment the issue with the text, or with the whole thread if the text is omitted")]
+    Comment {
+        issue: String,
+        #[arg(trailing_var_arg = true)]
+        text: Vec<String>,
+    },
+    #[command(about = "Move the issue to another status, e.g. `jira move ABC-123 \"In Progress\"`")]
+    Move {
+        issue: String,
+        #[arg(trailing_var_arg = true, required = true)]
+        status: Vec<String>,
+    },
+    #[command(about = "Search issues with JQL, `jira search more` shows the next page")]
+    Search(SearchArgs),
+}
+
+#[derive(Debug, Args, Clone)]
+struct SearchArgs {
+    // issues assigned to the caller
+    #[arg(short, long)]
+    mine: bool,
+    #[arg(short, long)]
+    project: Option<String>,
+    #[arg(short = 'n', long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..=50))]
+    limit: u32,
+    #[arg(trailing_var_arg = true)]
+    jql: Vec<String>,
+}
+
+#[derive(Debug, Args, Clone)]
+struct CreateArgs {
+    // channel setting `jira.project` is used if omitted
     #[arg(short, long)]
-    project: String,
+    project: Option<String>,
     #[arg(short, long)]
     title: Option<String>,
     #[arg(short, long)]
     description: Option<String>,
+    // put the whole thread into the description instead of the root message
+    #[arg(long)]
+    thread: bool,
+    // don't attach files of the messages
+    #[arg(long)]
+    no_files: bool,
+    // project default or Task if omitted
+    #[arg(long = "type")]
+    issue_type: Option<String>,
+    #[arg(long)]
+    priority: Option<String>,
+    #[arg(short, long = "label")]
+    labels: Vec<String>,
+    #[arg(short, long = "component")]
+    components: Vec<String>,
+    // parent epic key
+    #[arg(short, long)]
+    epic: Option<String>,
+    // slack mention, e.g. `--assignee @someone`
+    #[arg(short, long)]
+    assignee: Option<String>,
 }
 
-#[allow(unused)]
-pub struct JiraHandler {
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JiraHandlerParams {
     host: String,
-    user: String,
+    user_email: String,
     token: String,
+    #[serde(default)]
+    projects: HashMap<String, JiraProjectDefaults>,
+    #[serde(default)]
+    users: HashMap<String, String>,
+    #[serde(default)]
+    max_attachment_size: Option<usize>,
+    #[serde(default)]
+    max_attachments_size: Option<usize>,
+    #[serde(default)]
+    webhook_secret: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct JiraHandlerOptions {
+    // project key -> fields of new issues, e.g. issue type or custom fields
+    pub projects: HashMap<String, JiraProjectDefaults>,
+    // slack user id -> jira accountId, for users not matched by email
+    pub users: HashMap<String, String>,
+    // limits of slack files attached to new issues in bytes: per file and per issue
+    pub max_attachment_size: usize,
+    pub max_attachments_size: usize,
+    // secret of jira webhooks sent to `/hooks/jira` of the ops server, not checked if None
+    pub webhook_secret: Option<String>,
+}
+
+impl Default for JiraHandlerOptions {
+    fn default() -> Self {
+        Self {
+            projects: HashMap::new(),
+            users: HashMap::new(),
+            max_attachment_size: attachments::DEFAULT_MAX_FILE_SIZE,
+            max_attachments_size: attachments::DEFAULT_MAX_TOTAL_SIZE,
+            webhook_secret: None,
+        }
+    }
+}
+
+pub struct JiraHandler {
+    client: JiraClient,
     supported_channels: HashSet<String>,
+    project_defaults: HashMap<String, JiraProjectDefaults>,
+    users: UserMapping,
+    attachment_limits: AttachmentLimits,
+    webhook_secret: Option<String>,
+    // accountId of the service user, changes made by the bot are not posted back to threads
+    service_account_id: OnceLock<String>,
 }
 
 #[async_trait]
@@ -34,7 +176,7 @@ impl MsgHandler for JiraHandler {
     }
 
     fn description(&self) -> &str {
-        "Create jira ticket"
+        "Create and update jira tickets, see `jira --help`"
     }
 
     fn supported_channels(&self) -> &HashSet<String> {
@@ -42,118 +184,539 @@ impl MsgHandler for JiraHandler {
     }
 
     async fn handle(&self, args: &[String], msg_ev: &SlackMsgEv, bot_state: &BotState) -> Result<()> {
-        let (channel, thread) = extract_channel_thread(msg_ev)?;
-
+        // `jira` alone offers the create dialog, modals can't be opened without a click
+        if args.len() == 1 {
+            let (channel, thread_ts) = extract_channel_thread(msg_ev)?;
+            let blocks = modal::open_button()?;
+            bot_state.slack_cli.send_blocks(&channel, Some(&thread_ts), modal::OPEN_TEXT, blocks).await?;
+            return Ok(());
+        }
         let parsed_args = match JiraHandlerArgs::try_parse_from(args) {
             Ok(args) => args,
-            Err(err) => {
-                bot_state.slack_cli.send_reply(&channel, &thread, &format!("{}", err)).await?;
-                return Ok(());
+            Err(err) => return Err(HandlerError::user(err.to_string()).into()),
+        };
+        match parsed_args.command {
+            None => self.handle_create(&parsed_args.create, msg_ev, bot_state).await,
+            Some(JiraCommand::Comment { issue, text }) => self.handle_comment(&issue, &text, msg_ev, bot_state).await,
+            Some(JiraCommand::Move { issue, status }) => {
+                self.handle_move(&issue, &status.join(" "), msg_ev, bot_state).await
+            }
+            Some(JiraCommand::Search(args)) => self.handle_search(&args, msg_ev, bot_state).await,
+        }
+    }
+
+    fn message_pattern(&self) -> Option<&Regex> {
+        Some(&unfurl::ISSUE_KEY)
+    }
+
+    // replies with short info of the mentioned issues
+    async fn handle_message(&self, matches: &[String], msg_ev: &SlackMsgEv, bot_state: &BotState) -> Result<()> {
+        let (channel, thread_ts) = extract_channel_thread(msg_ev)?;
+        if bot_state.typed_channel_setting::<bool>(&channel, unfurl::UNFURL_SETTING).await? == Some(false) {
+            return Ok(());
+        }
+        let mut lines = vec![];
+        for key in matches.iter().take(unfurl::MAX_UNFURLS) {
+            if let Some(issue) = unfurl::cached_issue(&self.client, bot_state, key).await? {
+                lines.push(unfurl::format_issue(&self.client, key, &issue));
             }
+        }
+        if lines.is_empty() {
+            return Ok(());
+        }
+        bot_state.slack_cli.send_reply(&channel, &thread_ts, &lines.join("\n")).await
+    }
+
+    async fn handle_interaction(&self, event: &SlackInteractionEvent, bot_state: &BotState) -> Result<()> {
+        match event {
+            SlackInteractionEvent::BlockActions(event) => self.open_create_dialog(event, bot_state).await,
+            SlackInteractionEvent::ViewSubmission(event) => self.submit_create_dialog(event, bot_state).await,
+            _ => Ok(()),
+        }
+    }
+
+    // jira webhook with issue and comment events, posted to the linked threads
+    async fn handle_webhook(&self, request: &WebhookRequest, workspaces: &Workspaces) -> Result<()> {
+        // anyone reaching the ops port could post to the threads otherwise
+        let Some(secret) = &self.webhook_secret else {
+            return Err(HandlerError::unauthorized("Jira webhooks are disabled: webhook_secret is not set").into());
         };
-        self.handle_create(&parsed_args, msg_ev, bot_state).await
+        sync::verify(request, secret)?;
+        let event: Value = serde_json::from_slice(&request.body)
+            .map_err(|err| HandlerError::user(format!("Invalid webhook body: {err}")))?;
+        let service_account_id = self.service_account_id().await?;
+        sync::handle_event(&event, workspaces, &service_account_id).await
     }
 }
 
 impl JiraHandler {
-    pub fn make<I>(jira_host: &str, user_email: &str, user_token: &str, supported_channels: I) -> ArcMsgHandler
+    pub fn make<I>(
+        jira_host: &str,
+        user_email: &str,
+        user_token: &str,
+        supported_channels: I,
+        options: JiraHandlerOptions,
+    ) -> ArcMsgHandler
     where
         I: IntoIterator<Item = String>,
     {
-        let jira_host = jira_host.trim_end_matches('/');
         Arc::new(Self {
-            host: jira_host.into(),
-            user: user_email.into(),
-            token: user_token.into(),
+            client: JiraClient::new(jira_host, user_email, user_token),
             supported_channels: supported_channels.into_iter().collect(),
+            project_defaults: options.projects.into_iter().map(|(key, value)| (key.to_uppercase(), value)).collect(),
+            users: UserMapping::new(options.users),
+            attachment_limits: AttachmentLimits {
+                max_file_size: options.max_attachment_size,
+                max_total_size: options.max_attachments_size,
+            },
+            webhook_secret: options.webhook_secret,
+            service_account_id: OnceLock::new(),
         })
     }
 
-    async fn handle_create(&self, args: &JiraHandlerArgs, msg_ev: &SlackMsgEv, bot_state: &BotState) -> Result<()> {
+    // params: host, user_email, token, projects, users, max_attachment_size, max_attachments_size, webhook_secret
+    pub fn from_config(config: &HandlerConfig) -> Result<ArcMsgHandler> {
+        let params: JiraHandlerParams = config.params()?;
+        let channels = config.channels.iter().cloned();
+        let defaults = JiraHandlerOptions::default();
+        let options = JiraHandlerOptions {
+            projects: params.projects,
+            users: params.users,
+            max_attachment_size: params.max_attachment_size.unwrap_or(defaults.max_attachment_size),
+            max_attachments_size: params.max_attachments_size.unwrap_or(defaults.max_attachments_size),
+            // empty if taken from unset env variable
+            webhook_secret: params.webhook_secret.filter(|secret| !secret.is_empty()),
+        };
+        Ok(Self::make(&params.host, &params.user_email, &params.token, channels, options))
+    }
+
+    async fn service_account_id(&self) -> Result<String> {
+        if let Some(account_id) = self.service_account_id.get() {
+            return Ok(account_id.clone());
+        }
+        let response = self.client.get("/rest/api/3/myself", &[]).await?;
+        let account_id =
+            response["accountId"].as_str().ok_or_else(|| anyhow!("accountId is missing in jira response"))?;
+        Ok(self.service_account_id.get_or_init(|| account_id.to_string()).clone())
+    }
+
+    async fn handle_create(&self, args: &CreateArgs, msg_ev: &SlackMsgEv, bot_state: &BotState) -> Result<()> {
         let (channel, thread_ts) = extract_channel_thread(msg_ev)?;
 
         let mut args = args.clone();
+        if args.project.is_none() {
+            args.project = bot_state.channel_setting(&channel, PROJECT_SETTING).await?;
+        }
+        let project = match &args.project {
+            Some(project) => project.clone(),
+            None => {
+                let msg = format!("Project is required: use `--project` or `config set {PROJECT_SETTING} <KEY>`");
+                return Err(HandlerError::user(msg).into());
+            }
+        };
 
-        let root_msg = bot_state.slack_cli.get_msg(&channel, &thread_ts).await?.unwrap();
+        // the root may be deleted by the time a dialog is submitted
+        let root_msg = match bot_state.slack_cli.get_msg(&channel, &thread_ts).await? {
+            Some(root_msg) => root_msg,
+            None => return Err(HandlerError::user("Thread message not found").into()),
+        };
         let root_body = extract_msg_body(&root_msg)?;
         let root_body = root_body.strip_prefix(&bot_state.bot_marker).unwrap_or(&root_body);
 
         if args.title.is_none() {
-            let len = min(root_body.len(), 50);
-            args.title = Some(format!("slack: {}", &root_body[..len]));
+            args.title = Some(default_title(root_body));
+        }
+
+        let description = match (&args.description, args.thread) {
+            (Some(description), _) => mrkdwn::to_adf(description, &user_names(&[description], bot_state).await),
+            (None, true) => thread_blocks(msg_ev, bot_state).await?,
+            (None, false) => {
+                let mut blocks = vec![adf::paragraph(vec![adf::text("Slack message:")])];
+                blocks.extend(mrkdwn::to_adf(root_body, &user_names(&[root_body], bot_state).await));
+                blocks
+            }
+        };
+        let slack_msg_link = bot_state.slack_cli.get_permalink(&channel, &thread_ts).await?;
+
+        let assignee = match &args.assignee {
+            Some(mention) => match users::parse_mention(mention) {
+                Some(user) => Some(self.users.account_id(&self.client, bot_state, &user).await?),
+                None => return Err(HandlerError::user("Assignee must be a user mention, e.g. `@someone`").into()),
+            },
+            None => None,
+        };
+        // issue is still created by the service user if the caller has no jira account
+        let reporter = match &msg_ev.sender.user {
+            Some(user) => match self.users.account_id(&self.client, bot_state, user).await {
+                Ok(account_id) => Some(account_id),
+                Err(err) => {
+                    log::info!("reporter is not set, jira account of '{user}' is not found: {err:#}");
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let files = match (args.no_files, args.thread) {
+            (true, _) => vec![],
+            (false, true) => thread_files(msg_ev, bot_state).await?,
+            (false, false) => root_msg.content.files.clone().unwrap_or_default(),
+        };
+
+        let people = People { reporter, assignee };
+        let issue_key = self.create_issue(&project, &args, description, &people, &slack_msg_link).await?;
+        let link_note = sync::try_link_thread(bot_state, &issue_key, &channel, &thread_ts).await;
+        let mut msg = format!("Issue created: {}", self.client.issue_url(&issue_key));
+        let limits = self.attachment_limits;
+        if let Some(summary) = attachments::attach_files(&self.client, bot_state, &issue_key, &files, limits).await {
+            msg = format!("{msg}\n{summary}");
+        }
+        if let Some(note) = link_note {
+            msg = format!("{msg}\n{note}");
+        }
+        bot_state.slack_cli.send_reply(&channel, &thread_ts, &msg).await
+    }
+
+    async fn open_create_dialog(&self, event: &SlackInteractionBlockActionsEvent, bot_state: &BotState) -> Result<()> {
+        let clicked = event.actions.iter().flatten().any(|action| action.action_id.as_ref() == modal::OPEN_ACTION);
+        if !clicked {
+            return Ok(());
+        }
+        let channel = event.channel.as_ref().ok_or_else(|| anyhow!("channel is missing in block actions"))?.id.clone();
+        let button_msg = event.message.as_ref().ok_or_else(|| anyhow!("message is missing in block actions"))?;
+        let thread = modal::DialogThread {
+            channel: channel.to_string(),
+            thread_ts: button_msg.origin.thread_ts.as_ref().unwrap_or(&button_msg.origin.ts).to_string(),
+            msg_ts: button_msg.origin.ts.to_string(),
+        };
+
+        // trigger_id expires in 3 seconds, so the dialog is opened first and filled in when everything is fetched
+        let view_id = bot_state.slack_cli.open_view(&event.trigger_id, modal::message_view("Loading...")?).await?;
+        match self.create_dialog(&thread, bot_state).await {
+            Ok(view) => bot_state.slack_cli.update_view(&view_id, view).await,
+            Err(err) => {
+                let view = modal::message_view("Fail to load the dialog, check logs for details")?;
+                bot_state.slack_cli.update_view(&view_id, view).await?;
+                Err(err)
+            }
         }
+    }
+
+    async fn create_dialog(&self, thread: &modal::DialogThread, bot_state: &BotState) -> Result<SlackView> {
+        let channel = thread.channel.clone().into();
+        let root_msg = bot_state.slack_cli.get_msg(&channel, &thread.thread_ts.clone().into()).await?;
+        let root_body = match &root_msg {
+            Some(root_msg) => extract_msg_body(root_msg)?,
+            None => String::new(),
+        };
+        let root_body = root_body.strip_prefix(&bot_state.bot_marker).unwrap_or(&root_body).trim();
+        let project = bot_state.channel_setting(&channel, PROJECT_SETTING).await?;
+        let issue_type = project
+            .as_ref()
+            .and_then(|project| self.project_defaults.get(&project.to_uppercase()))
+            .and_then(|defaults| defaults.issue_type.clone());
+        // the key is typed in if projects can't be listed
+        let projects = match modal::cached_projects(&self.client, bot_state).await {
+            Ok(projects) => projects,
+            Err(err) => {
+                log::warn!("fail to get jira projects: {err:#}");
+                vec![]
+            }
+        };
+
+        let defaults = modal::DialogDefaults {
+            projects,
+            project,
+            issue_type,
+            summary: default_title(root_body),
+            description: root_body.to_string(),
+        };
+        modal::create_view(&defaults, thread)
+    }
 
-        if args.description.is_none() {
-            let description = format!(
-                "\
-                Slack message:\n\
-                {root_body}\n\n"
-            );
-            args.description = Some(description);
+    async fn submit_create_dialog(
+        &self,
+        event: &SlackInteractionViewSubmissionEvent,
+        bot_state: &BotState,
+    ) -> Result<()> {
+        let is_create = match &event.view.view {
+            SlackView::Modal(view) => view.callback_id.as_ref().is_some_and(|id| id.as_ref() == modal::CREATE_CALLBACK),
+            _ => false,
+        };
+        if !is_create {
+            return Ok(());
+        }
+        let (thread, flags) = modal::submitted(event)?;
+        // the dialog acts like `jira --project=.. --title=..` posted in the thread by the submitter
+        let origin = SlackMessageOrigin::new(thread.msg_ts.into())
+            .with_channel(thread.channel.into())
+            .with_thread_ts(thread.thread_ts.into());
+        let msg_ev = SlackMsgEv::new(origin, SlackMessageSender::new().with_user(event.user.id.clone()));
+        let args = [vec![self.name().to_string()], flags].concat();
+        bot_state.run_command(&args, &msg_ev).await
+    }
+
+    async fn handle_comment(
+        &self,
+        issue: &str,
+        text: &[String],
+        msg_ev: &SlackMsgEv,
+        bot_state: &BotState,
+    ) -> Result<()> {
+        let (channel, thread_ts) = extract_channel_thread(msg_ev)?;
+        let issue = issue_key(issue)?;
+
+        let mut content = match text.is_empty() {
+            true => thread_blocks(msg_ev, bot_state).await?,
+            false => {
+                let text = text.join(" ");
+                mrkdwn::to_adf(&text, &user_names(&[&text], bot_state).await)
+            }
+        };
+        if content.is_empty() {
+            return Err(HandlerError::user("Nothing to comment: the thread is empty").into());
         }
         let slack_msg_link = bot_state.slack_cli.get_permalink(&channel, &thread_ts).await?;
+        content.push(adf::paragraph(vec![adf::link("[Slack thread link]", &slack_msg_link)]));
 
-        let issue_url = self.create_issue(&args, &slack_msg_link).await?;
-        let msg = format!("Issue created: {issue_url}");
+        let body = json!({ "body": adf::doc(content) });
+        self.client.post(&format!("/rest/api/3/issue/{issue}/comment"), &body).await?;
+        let mut msg = format!("Comment added: {}", self.client.issue_url(&issue));
+        if let Some(note) = sync::try_link_thread(bot_state, &issue, &channel, &thread_ts).await {
+            msg = format!("{msg}\n{note}");
+        }
         bot_state.slack_cli.send_reply(&channel, &thread_ts, &msg).await
     }
 
-    async fn create_issue(&self, args: &JiraHandlerArgs, slack_msg_link: &str) -> Result<String> {
-        let url = format!("{}/rest/api/3/issue", self.host);
-        let empty_description = String::from("No description provided");
-        let body = json!({
-            "fields": {
-                "project": {
-                    "key": args.project.to_uppercase()
-                },
-                "summary": args.title,
-                "description": {
-                "content": [
-                    {
-                      "content": [
-                        {
-                          "text": args.description.as_ref().unwrap_or(&empty_description),
-                          "type": "text"
-                        },
-                        {
-                            "type": "text",
-                            "text": "[Slack message link]",
-                            "marks": [
-                            {
-                                "type": "link",
-                                "attrs": {
-                                    "href": slack_msg_link
-                                }
-                            }
-                            ]
-                        }
-                      ],
-                      "type": "paragraph"
-                    }
-                ],
-              "type": "doc",
-              "version": 1
-            },
-                "issuetype": {
-                    "name": "Task"
-                },
+    async fn handle_move(&self, issue: &str, status: &str, msg_ev: &SlackMsgEv, bot_state: &BotState) -> Result<()> {
+        let (channel, thread_ts) = extract_channel_thread(msg_ev)?;
+        let issue = issue_key(issue)?;
+
+        let path = format!("/rest/api/3/issue/{issue}/transitions");
+        let response = self.client.get(&path, &[]).await?;
+        let transitions = response["transitions"].as_array().cloned().unwrap_or_default();
+        let transition = match transitions::find(&transitions, status) {
+            Some(transition) => transition,
+            None => return Err(HandlerError::user(transitions::unavailable(&issue, status, &transitions)).into()),
+        };
+
+        let body = json!({ "transition": { "id": transition["id"] } });
+        self.client.post(&path, &body).await?;
+        let new_status = transition["to"]["name"].as_str().unwrap_or(status);
+        let msg = format!("{issue} moved to `{new_status}`: {}", self.client.issue_url(&issue));
+        bot_state.slack_cli.send_reply(&channel, &thread_ts, &msg).await
+    }
+
+    async fn handle_search(&self, args: &SearchArgs, msg_ev: &SlackMsgEv, bot_state: &BotState) -> Result<()> {
+        let (channel, thread_ts) = extract_channel_thread(msg_ev)?;
+        let state_key = format!("{channel}:{thread_ts}");
+
+        let page = match args.jql.as_slice() {
+            [more] if more == "more" => match bot_state.storage.get_json(SEARCH_NAMESPACE, &state_key).await? {
+                Some(page) => page,
+                None => {
+                    return Err(HandlerError::user("Nothing to continue: no more search results in this thread").into())
+                }
             },
-            "update": {},
-        });
-        log::debug!("creating jira issue: url={}, body={:?}", url, body.to_string());
-
-        let rsp =
-            reqwest::Client::new().post(&url).json(&body).basic_auth(&self.user, Some(&self.token)).send().await?;
-
-        let status = rsp.status();
-        let response: Value = serde_json::from_str(&rsp.text().await?)?;
-        log::debug!("jira issue created: status={}, response={}", status, response.to_string());
-        if status.is_success() {
-            let issue_key = response["key"].as_str().unwrap();
-            let issue_url = format!("{}/browse/{}", self.host, issue_key);
-            Ok(issue_url)
-        } else {
-            bail!("Jira API call error: status: {}, msg: {}", status, response.to_string());
+            _ => {
+                let assignee = match (args.mine, &msg_ev.sender.user) {
+                    (true, Some(user)) => Some(self.users.account_id(&self.client, bot_state, user).await?),
+                    _ => None,
+                };
+                let jql = search::build_jql(&args.jql.join(" "), args.project.as_deref(), assignee.as_deref());
+                if jql.to_ascii_lowercase().starts_with("order by") {
+                    let msg = "Search query is required: use JQL, `--mine` or `--project`";
+                    return Err(HandlerError::user(msg).into());
+                }
+                SearchPage {
+                    jql,
+                    limit: args.limit,
+                    next_page_token: None,
+                }
+            }
+        };
+
+        let result = search::search(&self.client, &page).await?;
+        let msg = match result.next_page_token {
+            Some(token) => {
+                let next_page = SearchPage {
+                    next_page_token: Some(token),
+                    ..page
+                };
+                bot_state.storage.set_json(SEARCH_NAMESPACE, &state_key, &next_page, Some(SEARCH_TTL)).await?;
+                format!("{}\nUse `jira search more` for the next {} issues", result.text, next_page.limit)
+            }
+            None => {
+                bot_state.storage.delete(SEARCH_NAMESPACE, &state_key).await?;
+                result.text
+            }
+        };
+        bot_state.slack_cli.send_reply(&channel, &thread_ts, &msg).await
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(%project))]
+    async fn create_issue(
+        &self,
+        project: &str,
+        args: &CreateArgs,
+        mut description: Vec<Value>,
+        people: &People,
+        slack_msg_link: &str,
+    ) -> Result<String> {
+        if description.is_empty() {
+            description.push(adf::paragraph(vec![adf::text("No description provided")]));
+        }
+        description.push(adf::paragraph(vec![adf::link("[Slack message link]", slack_msg_link)]));
+        let project = project.to_uppercase();
+
+        let mut fields = fields::issue_fields(self.project_defaults.get(&project), args);
+        fields.insert("project".into(), json!({ "key": project }));
+        fields.insert("summary".into(), json!(args.title));
+        if let Some(reporter) = &people.reporter {
+            fields.insert("reporter".into(), json!({ "id": reporter }));
+        }
+        if let Some(assignee) = &people.assignee {
+            fields.insert("assignee".into(), json!({ "id": assignee }));
+        }
+        fields.insert("description".into(), adf::doc(description));
+        // without "Modify Reporter" permission the issue is created by the service user only
+        let created = match people.reporter {
+            Some(_) => {
+                self.client.post_unless_rejected("/rest/api/3/issue", &json!({ "fields": fields }), "reporter").await?
+            }
+            None => None,
+        };
+        let response = match created {
+            Some(response) => response,
+            None => {
+                fields.remove("reporter");
+                self.client.post("/rest/api/3/issue", &json!({ "fields": fields })).await?
+            }
+        };
+        let issue_key = response["key"].as_str().ok_or_else(|| anyhow!("Issue key is missing in jira response"))?;
+        Ok(issue_key.to_string())
+    }
+}
+
+// issue key given by the user, it goes into REST paths so nothing else is accepted
+fn issue_key(issue: &str) -> Result<String> {
+    let issue = issue.to_uppercase();
+    match unfurl::ISSUE_KEY.find(&issue) {
+        Some(found) if found.as_str() == issue => Ok(issue),
+        _ => Err(HandlerError::user(format!("Invalid issue key '{issue}', expected e.g. ABC-123")).into()),
+    }
+}
+
+// summary of the issue created from the message
+fn default_title(msg_body: &str) -> String {
+    let first_line = msg_body.trim().lines().next().unwrap_or_default();
+    format!("slack: {}", first_line.chars().take(50).collect::<String>())
+}
+
+// jira accountIds of the new issue
+struct People {
+    reporter: Option<String>,
+    assignee: Option<String>,
+}
+
+// thread messages with authors and times, without bot messages and the command itself
+async fn thread_blocks(msg_ev: &SlackMsgEv, bot_state: &BotState) -> Result<Vec<Value>> {
+    let (channel, thread_ts) = extract_channel_thread(msg_ev)?;
+    let command_ts = extract_msg_ts(msg_ev);
+    let thread = bot_state.slack_cli.get_thread(&channel, &thread_ts).await?;
+
+    let messages = thread
+        .iter()
+        .filter(|msg| msg.sender.bot_id.is_none() && msg.origin.ts != command_ts)
+        .filter_map(|msg| msg.content.text.as_ref().filter(|text| !text.trim().is_empty()).map(|text| (msg, text)))
+        .collect::<Vec<_>>();
+    // authors are resolved along with mentions
+    let authors = messages
+        .iter()
+        .filter_map(|(msg, _)| msg.sender.user.as_ref().map(|user| format!("<@{user}>")))
+        .collect::<Vec<_>>();
+    let texts = messages.iter().map(|(_, text)| text.as_str()).chain(authors.iter().map(String::as_str));
+    let names = user_names(&texts.collect::<Vec<_>>(), bot_state).await;
+
+    let mut blocks = vec![];
+    for (msg, text) in messages {
+        let author = match &msg.sender.user {
+            Some(user) => names.get(user.as_ref()).cloned().unwrap_or(user.to_string()),
+            None => "unknown".to_string(),
+        };
+        let time = msg.origin.ts.as_ref().split('.').next().and_then(|secs| secs.parse().ok());
+        let time = time.and_then(|secs| chrono::DateTime::from_timestamp(secs, 0));
+        let mut header = vec![adf::marked_text(&author, &[adf::mark("strong")])];
+        if let Some(time) = time {
+            header.push(adf::text(&format!(" {}", time.format("%Y-%m-%d %H:%M UTC"))));
+        }
+        blocks.push(adf::paragraph(header));
+        blocks.extend(mrkdwn::to_adf(text, &names));
+    }
+    Ok(blocks)
+}
+
+// files of the thread messages, without bot messages
+async fn thread_files(msg_ev: &SlackMsgEv, bot_state: &BotState) -> Result<Vec<SlackFile>> {
+    let (channel, thread_ts) = extract_channel_thread(msg_ev)?;
+    let thread = bot_state.slack_cli.get_thread(&channel, &thread_ts).await?;
+    let files = thread.into_iter().filter(|msg| msg.sender.bot_id.is_none()).flat_map(|msg| msg.content.files);
+    Ok(files.flatten().collect())
+}
+
+// slack user id -> name of users mentioned in the texts, unknown users are skipped
+async fn user_names(texts: &[&str], bot_state: &BotState) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    for text in texts {
+        for user in mrkdwn::mentioned_users(text) {
+            if names.contains_key(&user) {
+                continue;
+            }
+            let profile = match bot_state.slack_cli.get_user(&user.clone().into()).await {
+                Ok(slack_user) => slack_user,
+                Err(err) => {
+                    log::debug!("fail to get name of user '{user}': {err:#}");
+                    continue;
+                }
+            };
+            let display_name = profile.profile.as_ref().and_then(|profile| profile.display_name.clone());
+            let real_name = profile.profile.as_ref().and_then(|profile| profile.real_name.clone());
+            let name = [display_name, real_name, profile.name].into_iter().flatten().find(|name| !name.is_empty());
+            if let Some(name) = name {
+                names.insert(user, name);
+            }
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_issue_keys() {
+        assert_eq!(issue_key("ABC-123").unwrap(), "ABC-123");
+        assert_eq!(issue_key("abc-1").unwrap(), "ABC-1");
+        assert_eq!(issue_key("A2B-7").unwrap(), "A2B-7");
+    }
+
+    #[test]
+    fn rejects_anything_else_in_issue_keys() {
+        for issue in [
+            "ABC",
+            "123",
+            "ABC-",
+            "ABC-12/comment",
+            "../ABC-1",
+            "ABC-1 ABC-2",
+            "1BC-1",
+            "",
+        ] {
+            let err = issue_key(issue).unwrap_err();
+            let expected = format!("Invalid issue key '{}', expected e.g. ABC-123", issue.to_uppercase());
+            assert_eq!(err.to_string(), expected);
+            assert!(matches!(err.downcast_ref::<HandlerError>(), Some(HandlerError::User(_))));
         }
     }
 }
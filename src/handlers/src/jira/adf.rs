@@ -0,0 +1,84 @@
+use serde_json::{json, Value};
+
+// Atlassian Document Format nodes, used for descriptions and comments
+
+pub(super) fn doc(content: Vec<Value>) -> Value {
+    json!({
+        "type": "doc",
+        "version": 1,
+        "content": content,
+    })
+}
+
+pub(super) fn paragraph(content: Vec<Value>) -> Value {
+    json!({
+        "type": "paragraph",
+        "content": content,
+    })
+}
+
+pub(super) fn text(text: &str) -> Value {
+    json!({
+        "type": "text",
+        "text": text,
+    })
+}
+
+pub(super) fn link(text: &str, href: &str) -> Value {
+    json!({
+        "type": "text",
+        "text": text,
+        "marks": [{"type": "link", "attrs": {"href": href}}],
+    })
+}
+
+// text with marks, e.g. strong, em, strike, code or link
+pub(super) fn marked_text(text: &str, marks: &[Value]) -> Value {
+    match marks.is_empty() {
+        true => self::text(text),
+        false => json!({
+            "type": "text",
+            "text": text,
+            "marks": marks,
+        }),
+    }
+}
+
+pub(super) fn mark(kind: &str) -> Value {
+    json!({ "type": kind })
+}
+
+pub(super) fn link_mark(href: &str) -> Value {
+    json!({ "type": "link", "attrs": { "href": href } })
+}
+
+pub(super) fn hard_break() -> Value {
+    json!({ "type": "hardBreak" })
+}
+
+pub(super) fn code_block(code: &str) -> Value {
+    let content = match code.is_empty() {
+        true => vec![],
+        false => vec![text(code)],
+    };
+    json!({
+        "type": "codeBlock",
+        "content": content,
+    })
+}
+
+pub(super) fn blockquote(content: Vec<Value>) -> Value {
+    json!({
+        "type": "blockquote",
+        "content": content,
+    })
+}
+
+// items are inline contents of the list items
+pub(super) fn list(ordered: bool, items: Vec<Vec<Value>>) -> Value {
+    let items = items.into_iter().map(|item| json!({ "type": "listItem", "content": [paragraph(item)] }));
+    json!({
+        "type": if ordered { "orderedList" } else { "bulletList" },
+        "content": items.collect::<Vec<_>>(),
+    })
+}
@@ -0,0 +1,73 @@
+use super::client::JiraClient;
+use slack_cmd_core::BotState;
+use slack_morphism::SlackFile;
+
+pub(super) const DEFAULT_MAX_FILE_SIZE: usize = 10 * 1024 * 1024;
+pub(super) const DEFAULT_MAX_TOTAL_SIZE: usize = 50 * 1024 * 1024;
+
+// size limits of files attached to a single issue, in bytes
+#[derive(Debug, Clone, Copy)]
+pub(super) struct AttachmentLimits {
+    pub max_file_size: usize,
+    pub max_total_size: usize,
+}
+
+// uploads slack files to the issue and returns a summary for the reply, None if there were no files.
+// failed files are reported, but don't fail the command: the issue is already created
+pub(super) async fn attach_files(
+    client: &JiraClient,
+    bot_state: &BotState,
+    issue_key: &str,
+    files: &[SlackFile],
+    limits: AttachmentLimits,
+) -> Option<String> {
+    if files.is_empty() {
+        return None;
+    }
+    let mut attached = vec![];
+    let mut skipped = vec![];
+    let mut total_size = 0;
+    for file in files {
+        let name = file.name.clone().or(file.title.clone()).unwrap_or(file.id.to_string());
+        let url = match &file.url_private_download {
+            Some(url) => url,
+            None => {
+                skipped.push(format!("{name} (not downloadable)"));
+                continue;
+            }
+        };
+        let max_size = limits.max_file_size.min(limits.max_total_size - total_size);
+        let content = match bot_state.slack_cli.download_file(url.as_str(), max_size).await {
+            Ok(Some(content)) => content,
+            Ok(None) => {
+                skipped.push(format!("{name} (too large)"));
+                continue;
+            }
+            Err(err) => {
+                log::warn!("fail to download slack file '{}': {err:#}", file.id);
+                skipped.push(format!("{name} (download failed)"));
+                continue;
+            }
+        };
+        let size = content.len();
+        match client.upload(&format!("/rest/api/3/issue/{issue_key}/attachments"), &name, content).await {
+            Ok(_) => {
+                total_size += size;
+                attached.push(name);
+            }
+            Err(err) => {
+                log::warn!("fail to attach file '{}' to {issue_key}: {err:#}", file.id);
+                skipped.push(format!("{name} (upload failed)"));
+            }
+        }
+    }
+
+    let mut summary = vec![];
+    if !attached.is_empty() {
+        summary.push(format!("Attached: {}", attached.join(", ")));
+    }
+    if !skipped.is_empty() {
+        summary.push(format!("Not attached: {}", skipped.join(", ")));
+    }
+    Some(summary.join("\n"))
+}
@@ -0,0 +1,146 @@
+use anyhow::Result;
+use reqwest::multipart::{Form, Part};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde_json::Value;
+use slack_cmd_core::{current_invocation_id, HandlerError};
+
+// Jira Cloud REST API v3 with basic auth of the service user
+pub(super) struct JiraClient {
+    host: String,
+    user: String,
+    token: String,
+    http: reqwest::Client,
+}
+
+impl JiraClient {
+    pub fn new(host: &str, user: &str, token: &str) -> Self {
+        Self {
+            host: host.trim_end_matches('/').into(),
+            user: user.into(),
+            token: token.into(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn issue_url(&self, issue_key: &str) -> String {
+        format!("{}/browse/{}", self.host, issue_key)
+    }
+
+    pub async fn get(&self, path: &str, query: &[(&str, &str)]) -> Result<Value> {
+        self.request(Method::GET, path, query, None).await
+    }
+
+    // None if the resource doesn't exist
+    pub async fn get_optional(&self, path: &str, query: &[(&str, &str)]) -> Result<Option<Value>> {
+        let (status, response) = self.send(Method::GET, path, query, None).await?;
+        match status {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(response)),
+            status => Err(jira_error(status, &response).into()),
+        }
+    }
+
+    pub async fn post(&self, path: &str, body: &Value) -> Result<Value> {
+        self.request(Method::POST, path, &[], Some(body)).await
+    }
+
+    // None if jira rejected the field with 400, e.g. reporter when the service user has no permission to set it
+    pub async fn post_unless_rejected(&self, path: &str, body: &Value, field: &str) -> Result<Option<Value>> {
+        let (status, response) = self.send(Method::POST, path, &[], Some(body)).await?;
+        match status {
+            StatusCode::BAD_REQUEST if response["errors"].get(field).is_some() => {
+                log::info!("jira rejected field '{field}': {}", response["errors"][field]);
+                Ok(None)
+            }
+            status if status.is_success() => Ok(Some(response)),
+            status => Err(jira_error(status, &response).into()),
+        }
+    }
+
+    // attachments are multipart uploads, jira requires X-Atlassian-Token to skip its XSRF check for them
+    #[tracing::instrument(level = "debug", skip(self, content))]
+    pub async fn upload(&self, path: &str, file_name: &str, content: Vec<u8>) -> Result<Value> {
+        let url = format!("{}{}", self.host, path);
+        log::debug!("jira upload: {}, file_name={}, size={}", url, file_name, content.len());
+        let form = Form::new().part("file", Part::bytes(content).file_name(file_name.to_string()));
+        let request = self.http.post(&url).multipart(form).header("X-Atlassian-Token", "no-check");
+        let (status, response) = self.execute(request).await?;
+        if !status.is_success() {
+            return Err(jira_error(status, &response).into());
+        }
+        Ok(response)
+    }
+
+    // path relative to the host, e.g. /rest/api/3/issue
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<&Value>,
+    ) -> Result<Value> {
+        let (status, response) = self.send(method, path, query, body).await?;
+        if !status.is_success() {
+            return Err(jira_error(status, &response).into());
+        }
+        Ok(response)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, body))]
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<&Value>,
+    ) -> Result<(StatusCode, Value)> {
+        let url = format!("{}{}", self.host, path);
+        log::debug!("jira request: {} {}, query={:?}, body={:?}", method, url, query, body.map(Value::to_string));
+
+        let mut request = self.http.request(method, &url).query(query);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        self.execute(request).await
+    }
+
+    async fn execute(&self, request: RequestBuilder) -> Result<(StatusCode, Value)> {
+        let mut request = request.basic_auth(&self.user, Some(&self.token));
+        // lets match jira access logs with bot logs
+        if let Some(invocation_id) = current_invocation_id() {
+            request = request.header("X-Request-Id", invocation_id);
+        }
+        let rsp = request.send().await.map_err(|err| HandlerError::upstream("Jira", err.without_url().to_string()))?;
+
+        let status = rsp.status();
+        let rsp_text = rsp.text().await?;
+        // error pages of proxies in front of jira are not json, and some calls return no content
+        let response = match rsp_text.is_empty() {
+            true => Value::Null,
+            false => serde_json::from_str(&rsp_text).unwrap_or(Value::String(rsp_text)),
+        };
+        log::debug!("jira response: status={}, response={}", status, response);
+        Ok((status, response))
+    }
+}
+
+// Jira reports invalid input, e.g. unknown project key, with 400 and the list of problems
+fn jira_error(status: StatusCode, response: &Value) -> HandlerError {
+    if status == StatusCode::NOT_FOUND {
+        return HandlerError::user("Jira issue or project not found");
+    }
+    if status != StatusCode::BAD_REQUEST {
+        log::warn!("Jira API call error: status: {}, msg: {}", status, response);
+        return HandlerError::upstream("Jira", format!("status {status}"));
+    }
+    let mut problems = response["errorMessages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|msg| msg.as_str().map(str::to_string))
+        .collect::<Vec<_>>();
+    if let Some(errors) = response["errors"].as_object() {
+        problems.extend(errors.iter().map(|(field, msg)| format!("{field}: {}", msg.as_str().unwrap_or_default())));
+    }
+    HandlerError::user(format!("Jira rejected the request: {}", problems.join("; ")))
+}
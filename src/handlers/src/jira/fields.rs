@@ -0,0 +1,182 @@
+use super::CreateArgs;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+const DEFAULT_ISSUE_TYPE: &str = "Task";
+
+// Fields of new issues in the project, overridden by command args.
+// `fields` are sent as is, e.g. `customfield_10010 = { value = "Backend" }`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JiraProjectDefaults {
+    #[serde(default)]
+    pub issue_type: Option<String>,
+    #[serde(default)]
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub components: Vec<String>,
+    #[serde(default)]
+    pub fields: Map<String, Value>,
+}
+
+// optional fields of the new issue: template of the project first, then command args on top
+pub(super) fn issue_fields(defaults: Option<&JiraProjectDefaults>, args: &CreateArgs) -> Map<String, Value> {
+    let defaults = defaults.cloned().unwrap_or_default();
+    let mut fields = defaults.fields;
+
+    let issue_type = args.issue_type.as_deref().or(defaults.issue_type.as_deref()).unwrap_or(DEFAULT_ISSUE_TYPE);
+    fields.insert("issuetype".into(), json!({ "name": issue_type }));
+    if let Some(priority) = args.priority.as_ref().or(defaults.priority.as_ref()) {
+        fields.insert("priority".into(), json!({ "name": priority }));
+    }
+
+    let labels = merge(&defaults.labels, &args.labels);
+    if !labels.is_empty() {
+        fields.insert("labels".into(), json!(labels));
+    }
+    let components = merge(&defaults.components, &args.components);
+    if !components.is_empty() {
+        let components = components.iter().map(|name| json!({ "name": name })).collect::<Vec<_>>();
+        fields.insert("components".into(), json!(components));
+    }
+    if let Some(epic) = &args.epic {
+        fields.insert("parent".into(), json!({ "key": epic.to_uppercase() }));
+    }
+    fields
+}
+
+fn merge(defaults: &[String], extra: &[String]) -> Vec<String> {
+    let mut result = defaults.to_vec();
+    for item in extra {
+        if !result.contains(item) {
+            result.push(item.clone());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Command {
+        #[command(flatten)]
+        args: CreateArgs,
+    }
+
+    fn args(flags: &[&str]) -> CreateArgs {
+        Command::parse_from(std::iter::once("jira").chain(flags.iter().copied())).args
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    fn defaults() -> JiraProjectDefaults {
+        JiraProjectDefaults {
+            issue_type: Some("Bug".into()),
+            priority: Some("Medium".into()),
+            labels: strings(&["slack"]),
+            components: strings(&["Backend"]),
+            fields: Map::from_iter([("customfield_10010".to_string(), json!({ "value": "Ops" }))]),
+        }
+    }
+
+    #[test]
+    fn maps_fields() {
+        let cases: Vec<(&[&str], Option<JiraProjectDefaults>, Value)> = vec![
+            (&[], None, json!({ "issuetype": { "name": "Task" } })),
+            (
+                &[
+                    "--type",
+                    "Story",
+                    "--priority",
+                    "High",
+                    "-l",
+                    "a",
+                    "-l",
+                    "b",
+                    "-c",
+                    "Api",
+                    "-e",
+                    "ops-1",
+                ],
+                None,
+                json!({
+                    "issuetype": { "name": "Story" },
+                    "priority": { "name": "High" },
+                    "labels": ["a", "b"],
+                    "components": [{ "name": "Api" }],
+                    "parent": { "key": "OPS-1" },
+                }),
+            ),
+            (
+                &[],
+                Some(defaults()),
+                json!({
+                    "customfield_10010": { "value": "Ops" },
+                    "issuetype": { "name": "Bug" },
+                    "priority": { "name": "Medium" },
+                    "labels": ["slack"],
+                    "components": [{ "name": "Backend" }],
+                }),
+            ),
+            (
+                &[
+                    "--type",
+                    "Task",
+                    "--priority",
+                    "Low",
+                    "-l",
+                    "urgent",
+                    "-l",
+                    "slack",
+                    "-c",
+                    "Api",
+                ],
+                Some(defaults()),
+                json!({
+                    "customfield_10010": { "value": "Ops" },
+                    "issuetype": { "name": "Task" },
+                    "priority": { "name": "Low" },
+                    "labels": ["slack", "urgent"],
+                    "components": [{ "name": "Backend" }, { "name": "Api" }],
+                }),
+            ),
+            (
+                &["-e", "EPIC-7"],
+                Some(JiraProjectDefaults::default()),
+                json!({ "issuetype": { "name": "Task" }, "parent": { "key": "EPIC-7" } }),
+            ),
+        ];
+        for (flags, defaults, expected) in cases {
+            let fields = issue_fields(defaults.as_ref(), &args(flags));
+            assert_eq!(Value::Object(fields), expected, "{flags:?}");
+        }
+    }
+
+    #[test]
+    fn flags_override_fields_of_defaults() {
+        let mut defaults = defaults();
+        defaults.fields.insert("priority".into(), json!({ "name": "Lowest" }));
+        let fields = issue_fields(Some(&defaults), &args(&["--priority", "Highest"]));
+        assert_eq!(fields["priority"], json!({ "name": "Highest" }));
+    }
+
+    #[test]
+    fn merges_without_duplicates() {
+        let cases: [(&[&str], &[&str], &[&str]); 4] = [
+            (&[], &[], &[]),
+            (&["a"], &[], &["a"]),
+            (&[], &["b", "b"], &["b"]),
+            (&["a", "b"], &["b", "c"], &["a", "b", "c"]),
+        ];
+        for (defaults, extra, expected) in cases {
+            assert_eq!(merge(&strings(defaults), &strings(extra)), strings(expected));
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use super::client::JiraClient;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use slack_cmd_core::BotState;
+use slack_morphism::prelude::{SlackBlock, SlackInteractionViewSubmissionEvent, SlackView};
+use std::time::Duration;
+
+// button under `jira` command, opens the create dialog
+pub(super) const OPEN_ACTION: &str = "jira:open_create";
+pub(super) const CREATE_CALLBACK: &str = "jira:create";
+pub(super) const OPEN_TEXT: &str = "Create a Jira issue from this thread";
+const DIALOG_TITLE: &str = "Create Jira issue";
+
+const ISSUE_TYPES: [&str; 3] = ["Task", "Bug", "Story"];
+const PRIORITIES: [&str; 5] = ["Highest", "High", "Medium", "Low", "Lowest"];
+// slack limits of the dialog
+const MAX_OPTIONS: usize = 100;
+const MAX_OPTION_LEN: usize = 75;
+const MAX_SUMMARY_LEN: usize = 255;
+const MAX_DESCRIPTION_LEN: usize = 3000;
+// project list of the dropdown
+const PROJECTS_NAMESPACE: &str = "jira:projects";
+const PROJECTS_TTL: Duration = Duration::from_secs(3600);
+
+// thread of the dialog, kept in private_metadata of the view
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct DialogThread {
+    pub channel: String,
+    pub thread_ts: String,
+    // message with the button
+    pub msg_ts: String,
+}
+
+// initial values of the dialog
+pub(super) struct DialogDefaults {
+    // key, name
+    pub projects: Vec<(String, String)>,
+    pub project: Option<String>,
+    pub issue_type: Option<String>,
+    pub summary: String,
+    pub description: String,
+}
+
+pub(super) fn open_button() -> Result<Vec<SlackBlock>> {
+    let blocks = json!([
+        {
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": OPEN_TEXT },
+        },
+        {
+            "type": "actions",
+            "elements": [{
+                "type": "button",
+                "action_id": OPEN_ACTION,
+                "style": "primary",
+                "text": { "type": "plain_text", "text": "Create issue" },
+            }],
+        },
+    ]);
+    Ok(serde_json::from_value(blocks)?)
+}
+
+// placeholder shown while the dialog is loaded, or instead of it on failures
+pub(super) fn message_view(text: &str) -> Result<SlackView> {
+    let view = json!({
+        "type": "modal",
+        "title": { "type": "plain_text", "text": DIALOG_TITLE },
+        "close": { "type": "plain_text", "text": "Close" },
+        "blocks": [{ "type": "section", "text": { "type": "plain_text", "text": text } }],
+    });
+    Ok(serde_json::from_value(view)?)
+}
+
+pub(super) fn create_view(defaults: &DialogDefaults, thread: &DialogThread) -> Result<SlackView> {
+    // without the projects list the key is typed in
+    let project_element = match defaults.projects.is_empty() {
+        true => text_input(defaults.project.as_deref().unwrap_or_default(), false, MAX_OPTION_LEN),
+        false => {
+            let projects = defaults.projects.iter().take(MAX_OPTIONS);
+            let projects = projects.map(|(key, name)| (key.clone(), format!("{key}: {name}"))).collect::<Vec<_>>();
+            select(&projects, defaults.project.as_deref().map(str::to_uppercase).as_deref())
+        }
+    };
+    let mut issue_types = ISSUE_TYPES.map(String::from).to_vec();
+    let issue_type = defaults.issue_type.clone().unwrap_or(ISSUE_TYPES[0].to_string());
+    if !issue_types.contains(&issue_type) {
+        issue_types.insert(0, issue_type.clone());
+    }
+    let issue_types = issue_types.into_iter().map(|name| (name.clone(), name)).collect::<Vec<_>>();
+    let priorities = PRIORITIES.map(|name| (name.to_string(), name.to_string()));
+
+    let view = json!({
+        "type": "modal",
+        "callback_id": CREATE_CALLBACK,
+        "private_metadata": serde_json::to_string(thread)?,
+        "title": { "type": "plain_text", "text": DIALOG_TITLE },
+        "submit": { "type": "plain_text", "text": "Create" },
+        "close": { "type": "plain_text", "text": "Cancel" },
+        "blocks": [
+            input("project", "Project", project_element, false),
+            input("summary", "Summary", text_input(&defaults.summary, false, MAX_SUMMARY_LEN), false),
+            input("description", "Description", text_input(&defaults.description, true, MAX_DESCRIPTION_LEN), true),
+            input("type", "Type", select(&issue_types, Some(&issue_type)), false),
+            input("priority", "Priority", select(&priorities, None), true),
+        ],
+    });
+    Ok(serde_json::from_value(view)?)
+}
+
+// thread and `jira` create flags of the submitted dialog, `--flag=value` keeps values starting with `-` intact
+pub(super) fn submitted(event: &SlackInteractionViewSubmissionEvent) -> Result<(DialogThread, Vec<String>)> {
+    let metadata = match &event.view.view {
+        SlackView::Modal(view) => view.private_metadata.as_deref(),
+        SlackView::Home(_) => None,
+    };
+    let thread = serde_json::from_str(metadata.ok_or_else(|| anyhow!("Dialog metadata is missing"))?)?;
+
+    let values = event.view.state_params.state.as_ref().map(|state| &state.values);
+    let value = |block_id: &str| {
+        let value = values?.get(&block_id.into())?.values().next()?;
+        let value = value.selected_option.as_ref().map(|option| option.value.clone()).or(value.value.clone());
+        value.filter(|value| !value.trim().is_empty())
+    };
+    let flags = [
+        ("project", value("project").map(|project| project.trim().to_string())),
+        ("title", value("summary")),
+        ("description", value("description")),
+        ("type", value("type")),
+        ("priority", value("priority")),
+    ];
+    let args = flags.into_iter().filter_map(|(flag, value)| Some(format!("--{flag}={}", value?)));
+    Ok((thread, args.collect()))
+}
+
+// key and name of the projects visible to the service user
+pub(super) async fn cached_projects(client: &JiraClient, bot_state: &BotState) -> Result<Vec<(String, String)>> {
+    if let Some(projects) = bot_state.storage.get_json(PROJECTS_NAMESPACE, "all").await? {
+        return Ok(projects);
+    }
+    let max_results = MAX_OPTIONS.to_string();
+    let response = client.get("/rest/api/3/project/search", &[("maxResults", &max_results)]).await?;
+    let values = response["values"].as_array().cloned().unwrap_or_default();
+    let projects = values
+        .iter()
+        .filter_map(|project| Some((project["key"].as_str()?.to_string(), project["name"].as_str()?.to_string())))
+        .collect::<Vec<_>>();
+    bot_state.storage.set_json(PROJECTS_NAMESPACE, "all", &projects, Some(PROJECTS_TTL)).await?;
+    Ok(projects)
+}
+
+fn input(block_id: &str, label: &str, element: Value, optional: bool) -> Value {
+    json!({
+        "type": "input",
+        "block_id": block_id,
+        "optional": optional,
+        "label": { "type": "plain_text", "text": label },
+        "element": element,
+    })
+}
+
+fn text_input(initial: &str, multiline: bool, max_len: usize) -> Value {
+    let mut element = json!({
+        "type": "plain_text_input",
+        "action_id": "value",
+        "multiline": multiline,
+        "max_length": max_len,
+    });
+    if !initial.is_empty() {
+        element["initial_value"] = json!(initial.chars().take(max_len).collect::<String>());
+    }
+    element
+}
+
+// options are (value, text) pairs, the initial option is set only if it's one of them
+fn select(options: &[(String, String)], initial: Option<&str>) -> Value {
+    let mut element = json!({
+        "type": "static_select",
+        "action_id": "value",
+        "options": options.iter().map(|(value, text)| option(value, text)).collect::<Vec<_>>(),
+    });
+    if let Some((value, text)) = initial.and_then(|initial| options.iter().find(|(value, _)| value == initial)) {
+        element["initial_option"] = option(value, text);
+    }
+    element
+}
+
+fn option(value: &str, text: &str) -> Value {
+    json!({
+        "text": { "type": "plain_text", "text": text.chars().take(MAX_OPTION_LEN).collect::<String>() },
+        "value": value,
+    })
+}
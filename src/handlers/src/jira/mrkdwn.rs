@@ -0,0 +1,259 @@
+use super::adf;
+use super::unfurl::unescape;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+// `code`, <link|label>, *bold*, _italic_ and ~strike~
+static INLINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"`[^`\n]+`|<[^<>\n]+>|\*[^*\n]+\*|_[^_\n]+_|~[^~\n]+~").unwrap());
+static USER_MENTION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<@([A-Z0-9]+)(?:\|[^>]*)?>").unwrap());
+static ORDERED_ITEM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d+[.)] ").unwrap());
+
+// Converts Slack mrkdwn to ADF block nodes.
+// users: slack user id -> name, to show mentions as @name
+pub(super) fn to_adf(text: &str, users: &HashMap<String, String>) -> Vec<Value> {
+    let mut blocks = vec![];
+    // odd parts are code blocks
+    for (i, part) in text.split("```").enumerate() {
+        match i % 2 {
+            1 => blocks.push(adf::code_block(&unescape(part.trim_matches('\n')))),
+            _ => blocks.extend(text_blocks(part, users)),
+        }
+    }
+    blocks
+}
+
+// ids of users mentioned in the text
+pub(super) fn mentioned_users(text: &str) -> Vec<String> {
+    let mut users: Vec<String> = vec![];
+    for captures in USER_MENTION.captures_iter(text) {
+        if !users.iter().any(|user| user == &captures[1]) {
+            users.push(captures[1].to_string());
+        }
+    }
+    users
+}
+
+// paragraphs, lists and quotes, separated by lines
+fn text_blocks(text: &str, users: &HashMap<String, String>) -> Vec<Value> {
+    let mut blocks = vec![];
+    let mut paragraph: Vec<Value> = vec![];
+    // ordered flag and items of the current list
+    let mut list: Option<(bool, Vec<Vec<Value>>)> = None;
+
+    for line in text.lines() {
+        let item = list_item(line);
+        // list ends on other lines or items of the other kind
+        if list.as_ref().is_some_and(|(ordered, _)| item.map(|(item_ordered, _)| item_ordered) != Some(*ordered)) {
+            let (ordered, items) = list.take().unwrap();
+            blocks.push(adf::list(ordered, items));
+        }
+        if item.is_some() || line.trim().is_empty() || line.starts_with("&gt; ") {
+            flush_paragraph(&mut paragraph, &mut blocks);
+        }
+
+        match item {
+            Some((ordered, item)) => list.get_or_insert((ordered, vec![])).1.push(inline(item, users)),
+            None if line.trim().is_empty() => {}
+            // slack sends `>` escaped
+            None if line.starts_with("&gt; ") => {
+                let quote = adf::paragraph(inline(&line["&gt; ".len()..], users));
+                blocks.push(adf::blockquote(vec![quote]));
+            }
+            None => {
+                if !paragraph.is_empty() {
+                    paragraph.push(adf::hard_break());
+                }
+                paragraph.extend(inline(line, users));
+            }
+        }
+    }
+    flush_paragraph(&mut paragraph, &mut blocks);
+    if let Some((ordered, items)) = list {
+        blocks.push(adf::list(ordered, items));
+    }
+    blocks
+}
+
+fn flush_paragraph(paragraph: &mut Vec<Value>, blocks: &mut Vec<Value>) {
+    if !paragraph.is_empty() {
+        blocks.push(adf::paragraph(std::mem::take(paragraph)));
+    }
+}
+
+// (ordered, text) of `• item`, `- item` or `1. item` line
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    let line = line.trim_start();
+    for bullet in ["• ", "◦ ", "- "] {
+        if let Some(item) = line.strip_prefix(bullet) {
+            return Some((false, item));
+        }
+    }
+    ORDERED_ITEM.find(line).map(|found| (true, &line[found.end()..]))
+}
+
+// inline nodes with marks
+fn inline(text: &str, users: &HashMap<String, String>) -> Vec<Value> {
+    let mut nodes = vec![];
+    inline_marked(text, &[], users, &mut nodes);
+    nodes
+}
+
+fn inline_marked(text: &str, marks: &[Value], users: &HashMap<String, String>, nodes: &mut Vec<Value>) {
+    let mut plain_start = 0;
+    let mut pos = 0;
+    while let Some(found) = INLINE.find_at(text, pos) {
+        let token = found.as_str();
+        // formatting marks are ignored inside words, e.g. snake_case_name
+        let inside_word = text[..found.start()].chars().next_back().is_some_and(char::is_alphanumeric);
+        if inside_word && !token.starts_with('<') {
+            pos = found.start() + 1;
+            continue;
+        }
+        push_text(&text[plain_start..found.start()], marks, nodes);
+        let inner = &token[1..token.len() - 1];
+        match &token[..1] {
+            "`" => push_text(inner, &[adf::mark("code")], nodes),
+            "<" => push_entity(inner, marks, users, nodes),
+            marker => {
+                let kind = match marker {
+                    "*" => "strong",
+                    "_" => "em",
+                    _ => "strike",
+                };
+                let mut inner_marks = marks.to_vec();
+                inner_marks.push(adf::mark(kind));
+                inline_marked(inner, &inner_marks, users, nodes);
+            }
+        }
+        pos = found.end();
+        plain_start = pos;
+    }
+    push_text(&text[plain_start..], marks, nodes);
+}
+
+// <@U0123>, <#C0123|channel>, <!here>, <https://link|label>
+fn push_entity(entity: &str, marks: &[Value], users: &HashMap<String, String>, nodes: &mut Vec<Value>) {
+    let (target, label) = match entity.split_once('|') {
+        Some((target, label)) => (target, Some(label)),
+        None => (entity, None),
+    };
+    if let Some(user) = target.strip_prefix('@') {
+        let name = users.get(user).map(String::as_str).or(label).unwrap_or(user);
+        return push_text(&format!("@{name}"), marks, nodes);
+    }
+    if let Some(channel) = target.strip_prefix('#') {
+        return push_text(&format!("#{}", label.unwrap_or(channel)), marks, nodes);
+    }
+    if let Some(special) = target.strip_prefix('!') {
+        // user groups come with @ in the label: <!subteam^S0123|@team>
+        let name = label.unwrap_or_else(|| special.split('^').next().unwrap_or(special));
+        return push_text(&format!("@{}", name.trim_start_matches('@')), marks, nodes);
+    }
+    let mut link_marks = marks.to_vec();
+    link_marks.push(adf::link_mark(&unescape(target)));
+    push_text(label.unwrap_or(target), &link_marks, nodes);
+}
+
+// adf doesn't allow empty text nodes
+fn push_text(text: &str, marks: &[Value], nodes: &mut Vec<Value>) {
+    if !text.is_empty() {
+        nodes.push(adf::marked_text(&unescape(text), marks));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(text: &str) -> Vec<Value> {
+        to_adf(text, &HashMap::new())
+    }
+
+    fn marked(text: &str, kinds: &[&str]) -> Value {
+        adf::marked_text(text, &kinds.iter().map(|kind| adf::mark(kind)).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn converts_bold_italic_strike_and_code() {
+        let expected = adf::paragraph(vec![
+            marked("bold", &["strong"]),
+            adf::text(" "),
+            marked("italic", &["em"]),
+            adf::text(" "),
+            marked("strike", &["strike"]),
+            adf::text(" "),
+            marked("x = 1", &["code"]),
+        ]);
+        assert_eq!(convert("*bold* _italic_ ~strike~ `x = 1`"), vec![expected]);
+    }
+
+    #[test]
+    fn nests_marks() {
+        let expected = adf::paragraph(vec![marked("both", &["strong", "em"])]);
+        assert_eq!(convert("*_both_*"), vec![expected]);
+    }
+
+    #[test]
+    fn keeps_snake_case() {
+        let expected = adf::paragraph(vec![adf::text("call snake_case_name now")]);
+        assert_eq!(convert("call snake_case_name now"), vec![expected]);
+    }
+
+    #[test]
+    fn converts_code_blocks() {
+        let expected = vec![
+            adf::paragraph(vec![adf::text("before")]),
+            adf::code_block("let a = *b* && c;"),
+            adf::paragraph(vec![adf::text("after")]),
+        ];
+        assert_eq!(convert("before\n```\nlet a = *b* &amp;&amp; c;\n```\nafter"), expected);
+    }
+
+    #[test]
+    fn converts_lists() {
+        let expected = vec![
+            adf::list(false, vec![vec![adf::text("one")], vec![adf::text("two")]]),
+            adf::list(true, vec![vec![adf::text("first")]]),
+        ];
+        assert_eq!(convert("• one\n- two\n1. first"), expected);
+    }
+
+    #[test]
+    fn converts_quotes() {
+        let expected = vec![
+            adf::blockquote(vec![adf::paragraph(vec![adf::text("quoted")])]),
+            adf::paragraph(vec![adf::text("a > b")]),
+        ];
+        assert_eq!(convert("&gt; quoted\na &gt; b"), expected);
+    }
+
+    #[test]
+    fn converts_links() {
+        let link = |text: &str, href: &str| adf::marked_text(text, &[adf::link_mark(href)]);
+        let expected = adf::paragraph(vec![
+            link("docs", "https://example.com/?a=1&b=2"),
+            adf::text(" and "),
+            link("https://example.com", "https://example.com"),
+        ]);
+        assert_eq!(convert("<https://example.com/?a=1&amp;b=2|docs> and <https://example.com>"), vec![expected]);
+    }
+
+    #[test]
+    fn converts_mentions() {
+        let users = HashMap::from([("U1".to_string(), "alice".to_string())]);
+        let nodes = to_adf("<@U1> <@U2|bob> <#C1|general> <!here>", &users);
+        // adjacent plain texts come as separate nodes
+        let text = nodes[0]["content"].as_array().unwrap().iter().map(|node| node["text"].as_str().unwrap());
+        assert_eq!(text.collect::<String>(), "@alice @bob #general @here");
+        assert_eq!(mentioned_users("<@U1> <@U2|bob> <@U1>"), vec!["U1", "U2"]);
+    }
+
+    #[test]
+    fn joins_lines_with_hard_breaks() {
+        let expected = adf::paragraph(vec![adf::text("one"), adf::hard_break(), adf::text("two")]);
+        assert_eq!(convert("one\ntwo"), vec![expected]);
+    }
+}
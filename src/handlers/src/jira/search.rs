@@ -0,0 +1,130 @@
+use super::client::JiraClient;
+use super::unfurl::{escape, unescape};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// search state of the thread, to continue with `jira search more`
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct SearchPage {
+    pub jql: String,
+    pub limit: u32,
+    pub next_page_token: Option<String>,
+}
+
+pub(super) struct SearchResult {
+    pub text: String,
+    pub next_page_token: Option<String>,
+}
+
+// joins clauses with AND, keeping ORDER BY of the user query at the end.
+// The query comes from message text, so it's unescaped first
+pub(super) fn build_jql(query: &str, project: Option<&str>, assignee: Option<&str>) -> String {
+    let query = unescape(query);
+    let (query, order_by) = match order_by_position(&query) {
+        Some(pos) => (query[..pos].trim(), query[pos..].trim()),
+        None => (query.trim(), "ORDER BY updated DESC"),
+    };
+    let mut clauses = vec![];
+    if let Some(project) = project {
+        clauses.push(format!("project = \"{}\"", project.to_uppercase()));
+    }
+    if let Some(assignee) = assignee {
+        clauses.push(format!("assignee = \"{assignee}\""));
+    }
+    if !query.is_empty() {
+        clauses.push(format!("({query})"));
+    }
+    format!("{} {order_by}", clauses.join(" AND ")).trim().to_string()
+}
+
+// ORDER BY outside of quoted strings, e.g. not the one of `summary ~ "order by"`
+fn order_by_position(query: &str) -> Option<usize> {
+    let lowercase = query.to_ascii_lowercase();
+    let mut quote = None;
+    let mut escaped = false;
+    for (pos, ch) in lowercase.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if ch == '\\' => escaped = true,
+            Some(open) if ch == open => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if lowercase[pos..].starts_with("order by") => return Some(pos),
+            None => {}
+        }
+    }
+    None
+}
+
+pub(super) async fn search(client: &JiraClient, page: &SearchPage) -> Result<SearchResult> {
+    let mut body = json!({
+        "jql": page.jql,
+        "maxResults": page.limit,
+        "fields": ["summary", "status", "assignee"],
+    });
+    if let Some(token) = &page.next_page_token {
+        body["nextPageToken"] = json!(token);
+    }
+    let response = client.post("/rest/api/3/search/jql", &body).await?;
+
+    let issues = response["issues"].as_array().cloned().unwrap_or_default();
+    let rows = issues.iter().map(|issue| format_issue(client, issue)).collect::<Vec<_>>();
+    let next_page_token = match response["isLast"].as_bool().unwrap_or(true) {
+        true => None,
+        false => response["nextPageToken"].as_str().map(str::to_string),
+    };
+    let text = match rows.is_empty() {
+        true => "No issues found".to_string(),
+        false => rows.join("\n"),
+    };
+    Ok(SearchResult { text, next_page_token })
+}
+
+// one table row: key, status, assignee, summary
+fn format_issue(client: &JiraClient, issue: &Value) -> String {
+    let key = issue["key"].as_str().unwrap_or_default();
+    let fields = &issue["fields"];
+    let status = fields["status"]["name"].as_str().unwrap_or("-");
+    let assignee = fields["assignee"]["displayName"].as_str().unwrap_or("Unassigned");
+    let summary = escape(fields["summary"].as_str().unwrap_or_default());
+    format!("• <{}|{key}> | *{status}* | {assignee} | {summary}", client.issue_url(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_shortcuts_and_query() {
+        let jql = build_jql("status = Open", Some("ops"), Some("acc-1"));
+        assert_eq!(jql, "project = \"OPS\" AND assignee = \"acc-1\" AND (status = Open) ORDER BY updated DESC");
+    }
+
+    #[test]
+    fn keeps_order_by_of_query() {
+        let jql = build_jql("status = Open order by created ASC", Some("OPS"), None);
+        assert_eq!(jql, "project = \"OPS\" AND (status = Open) order by created ASC");
+    }
+
+    #[test]
+    fn unescapes_query() {
+        let jql = build_jql("created &gt;= -7d AND summary ~ \"a &amp; b\"", None, None);
+        assert_eq!(jql, "(created >= -7d AND summary ~ \"a & b\") ORDER BY updated DESC");
+    }
+
+    #[test]
+    fn ignores_quoted_order_by() {
+        let jql = build_jql("summary ~ \"order by\"", None, None);
+        assert_eq!(jql, "(summary ~ \"order by\") ORDER BY updated DESC");
+
+        let jql = build_jql("summary ~ 'it\\'s order by' ORDER BY key", None, None);
+        assert_eq!(jql, "(summary ~ 'it\\'s order by') ORDER BY key");
+    }
+
+    #[test]
+    fn sorts_by_update_without_clauses() {
+        assert_eq!(build_jql("  ", None, None), "ORDER BY updated DESC");
+        assert_eq!(build_jql("ORDER BY key", None, None), "ORDER BY key");
+    }
+}
@@ -0,0 +1,227 @@
+use super::unfurl::escape;
+use anyhow::Result;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use slack_cmd_core::{BotState, HandlerError, WebhookRequest, Workspaces};
+use slack_morphism::{SlackChannelId, SlackTs};
+
+// issue key -> slack thread it was created or commented from
+const LINKS_NAMESPACE: &str = "jira:threads";
+// long comments are cut in the thread
+const MAX_COMMENT_LEN: usize = 500;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ThreadLink {
+    team_id: String,
+    channel: String,
+    thread_ts: String,
+}
+
+// existing link is kept, so updates go to the thread the issue was created from
+async fn link_thread(
+    bot_state: &BotState,
+    issue_key: &str,
+    channel: &SlackChannelId,
+    thread_ts: &SlackTs,
+) -> Result<()> {
+    if bot_state.storage.get(LINKS_NAMESPACE, issue_key).await?.is_some() {
+        return Ok(());
+    }
+    let link = ThreadLink {
+        team_id: bot_state.team_id.to_string(),
+        channel: channel.to_string(),
+        thread_ts: thread_ts.to_string(),
+    };
+    bot_state.storage.set_json(LINKS_NAMESPACE, issue_key, &link, None).await
+}
+
+// the issue is already created or commented, so a failed link must not lose the reply with its url.
+// Returns a note about it for the reply
+pub(super) async fn try_link_thread(
+    bot_state: &BotState,
+    issue_key: &str,
+    channel: &SlackChannelId,
+    thread_ts: &SlackTs,
+) -> Option<String> {
+    match link_thread(bot_state, issue_key, channel, thread_ts).await {
+        Ok(_) => None,
+        Err(err) => {
+            log::warn!("fail to link {issue_key} to thread {channel}/{thread_ts}: {err:#}");
+            Some(format!("_Updates of {issue_key} won't be posted to this thread_"))
+        }
+    }
+}
+
+// Jira Cloud signs webhooks with the secret as `X-Hub-Signature: sha256=<hex>`,
+// automation rules can't sign requests, so `?secret=` is accepted as well
+pub(super) fn verify(request: &WebhookRequest, secret: &str) -> Result<()> {
+    if request.query.get("secret").is_some_and(|value| constant_time_eq(value.as_bytes(), secret.as_bytes())) {
+        return Ok(());
+    }
+    let signature = request.headers.get("x-hub-signature").and_then(|value| value.to_str().ok());
+    let signature = signature.and_then(|value| value.strip_prefix("sha256=")).and_then(|value| hex::decode(value).ok());
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+    mac.update(&request.body);
+    match signature {
+        Some(signature) if mac.verify_slice(&signature).is_ok() => Ok(()),
+        _ => Err(HandlerError::unauthorized("Invalid webhook signature").into()),
+    }
+}
+
+// doesn't stop at the first mismatch, so the secret can't be guessed byte by byte
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len() && left.iter().zip(right).fold(0, |acc, (l, r)| acc | (l ^ r)) == 0
+}
+
+// posts status, assignee and comment changes of linked issues to their threads.
+// changes made by the bot itself (service_account_id) are skipped, they are in the thread already
+pub(super) async fn handle_event(event: &Value, workspaces: &Workspaces, service_account_id: &str) -> Result<()> {
+    let issue_key = match event["issue"]["key"].as_str() {
+        Some(issue_key) => issue_key,
+        None => return Ok(()),
+    };
+    let primary = workspaces.primary();
+    let link: ThreadLink = match primary.storage.get_json(LINKS_NAMESPACE, issue_key).await? {
+        Some(link) => link,
+        None => return Ok(()),
+    };
+
+    let messages = match event["webhookEvent"].as_str().unwrap_or_default() {
+        "jira:issue_updated" if event["user"]["accountId"] != service_account_id => changelog_messages(event),
+        "comment_created" if event["comment"]["author"]["accountId"] != service_account_id => {
+            vec![comment_message(&event["comment"])]
+        }
+        "jira:issue_deleted" => {
+            primary.storage.delete(LINKS_NAMESPACE, issue_key).await?;
+            vec!["issue was deleted".to_string()]
+        }
+        _ => vec![],
+    };
+    if messages.is_empty() {
+        return Ok(());
+    }
+
+    let Some(bot_state) = workspaces.get(Some(&link.team_id.clone().into())) else {
+        log::warn!("{issue_key} is linked to a thread of workspace '{}', which is not served", link.team_id);
+        return Ok(());
+    };
+    let msg = messages.iter().map(|msg| format!("*{issue_key}* {msg}")).collect::<Vec<_>>().join("\n");
+    bot_state.slack_cli.send_reply(&link.channel.into(), &link.thread_ts.into(), &msg).await
+}
+
+fn changelog_messages(event: &Value) -> Vec<String> {
+    let author = escape(event["user"]["displayName"].as_str().unwrap_or("someone"));
+    let items = event["changelog"]["items"].as_array().cloned().unwrap_or_default();
+    let mut messages = vec![];
+    for item in items {
+        let from = item["fromString"].as_str().map(escape);
+        let to = item["toString"].as_str().map(escape);
+        match item["field"].as_str().unwrap_or_default() {
+            "status" => {
+                let from = from.unwrap_or("-".into());
+                let to = to.unwrap_or("-".into());
+                messages.push(format!("status: {from} → *{to}* (by {author})"));
+            }
+            "assignee" => match to {
+                Some(to) => messages.push(format!("assigned to *{to}* (by {author})")),
+                None => messages.push(format!("unassigned (by {author})")),
+            },
+            _ => {}
+        }
+    }
+    messages
+}
+
+fn comment_message(comment: &Value) -> String {
+    let author = escape(comment["author"]["displayName"].as_str().unwrap_or("someone"));
+    // comment body is plain text in webhooks, but ADF in some setups
+    let body = match &comment["body"] {
+        Value::String(body) => body.clone(),
+        body => adf_text(body),
+    };
+    let mut body = escape(body.trim());
+    if body.chars().count() > MAX_COMMENT_LEN {
+        body = format!("{}…", body.chars().take(MAX_COMMENT_LEN).collect::<String>());
+    }
+    let quote = body.lines().map(|line| format!("> {line}")).collect::<Vec<_>>().join("\n");
+    format!("new comment by {author}:\n{quote}")
+}
+
+// text of ADF nodes, paragraphs on separate lines
+fn adf_text(node: &Value) -> String {
+    if let Some(text) = node["text"].as_str() {
+        return text.to_string();
+    }
+    let children = node["content"].as_array().into_iter().flatten().map(adf_text);
+    match node["type"].as_str() {
+        Some("doc") | Some("bulletList") | Some("orderedList") => children.collect::<Vec<_>>().join("\n"),
+        _ => children.collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const SECRET: &str = "s3cret";
+    const BODY: &[u8] = br#"{"webhookEvent":"jira:issue_updated"}"#;
+
+    fn request(query_secret: Option<&str>, signature: Option<&str>) -> WebhookRequest {
+        let mut request = WebhookRequest {
+            headers: Default::default(),
+            query: HashMap::new(),
+            body: BODY.to_vec().into(),
+        };
+        if let Some(secret) = query_secret {
+            request.query.insert("secret".into(), secret.into());
+        }
+        if let Some(signature) = signature {
+            request.headers.insert("x-hub-signature", signature.parse().unwrap());
+        }
+        request
+    }
+
+    fn signature(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    fn is_unauthorized(result: Result<()>) -> bool {
+        matches!(result.unwrap_err().downcast_ref::<HandlerError>(), Some(HandlerError::Unauthorized(_)))
+    }
+
+    #[test]
+    fn accepts_signed_body() {
+        assert!(verify(&request(None, Some(&signature(SECRET, BODY))), SECRET).is_ok());
+    }
+
+    #[test]
+    fn accepts_query_secret() {
+        assert!(verify(&request(Some(SECRET), None), SECRET).is_ok());
+    }
+
+    #[test]
+    fn rejects_wrong_signature() {
+        assert!(is_unauthorized(verify(&request(None, Some(&signature("other", BODY))), SECRET)));
+        assert!(is_unauthorized(verify(&request(None, Some(&signature(SECRET, b"{}"))), SECRET)));
+        assert!(is_unauthorized(verify(&request(None, Some("sha256=zz")), SECRET)));
+    }
+
+    #[test]
+    fn rejects_wrong_or_missing_secret() {
+        assert!(is_unauthorized(verify(&request(Some("s3cre"), None), SECRET)));
+        assert!(is_unauthorized(verify(&request(Some("s3cret!"), None), SECRET)));
+        assert!(is_unauthorized(verify(&request(None, None), SECRET)));
+    }
+
+    #[test]
+    fn compares_in_constant_time() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+}
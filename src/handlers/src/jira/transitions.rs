@@ -0,0 +1,87 @@
+use serde_json::Value;
+
+// transition name usually matches the target status, but not always
+pub(super) fn find<'a>(transitions: &'a [Value], status: &str) -> Option<&'a Value> {
+    transitions.iter().find(|transition| {
+        [&transition["to"]["name"], &transition["name"]]
+            .iter()
+            .any(|name| name.as_str().is_some_and(|name| name.eq_ignore_ascii_case(status)))
+    })
+}
+
+// reply listing target statuses of the transitions, when none of them matches
+pub(super) fn unavailable(issue: &str, status: &str, transitions: &[Value]) -> String {
+    let mut available: Vec<String> = vec![];
+    for name in transitions.iter().filter_map(|transition| transition["to"]["name"].as_str()) {
+        let name = format!("`{name}`");
+        if !available.contains(&name) {
+            available.push(name);
+        }
+    }
+    match available.is_empty() {
+        true => format!("{issue} can't be moved to `{status}`: no transitions available"),
+        false => format!("{issue} can't be moved to `{status}`, available: {}", available.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn transitions() -> Vec<Value> {
+        vec![
+            json!({"id": "11", "name": "Start progress", "to": {"name": "In Progress"}}),
+            json!({"id": "21", "name": "Done", "to": {"name": "Done"}}),
+            json!({"id": "31", "name": "Resolve", "to": {"name": "Done"}}),
+        ]
+    }
+
+    fn found_id(status: &str) -> Option<String> {
+        let transitions = transitions();
+        find(&transitions, status).map(|transition| transition["id"].as_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn matches_target_status_ignoring_case() {
+        assert_eq!(found_id("In Progress"), Some("11".into()));
+        assert_eq!(found_id("in progress"), Some("11".into()));
+        assert_eq!(found_id("IN PROGRESS"), Some("11".into()));
+    }
+
+    #[test]
+    fn matches_transition_name_ignoring_case() {
+        assert_eq!(found_id("start progress"), Some("11".into()));
+        assert_eq!(found_id("RESOLVE"), Some("31".into()));
+    }
+
+    #[test]
+    fn first_transition_wins() {
+        assert_eq!(found_id("done"), Some("21".into()));
+    }
+
+    #[test]
+    fn no_partial_matches() {
+        assert_eq!(found_id("progress"), None);
+        assert_eq!(found_id(""), None);
+        assert_eq!(find(&[], "Done"), None);
+    }
+
+    #[test]
+    fn lists_distinct_target_statuses() {
+        assert_eq!(
+            unavailable("ABC-1", "Closed", &transitions()),
+            "ABC-1 can't be moved to `Closed`, available: `In Progress`, `Done`"
+        );
+    }
+
+    #[test]
+    fn reports_missing_transitions() {
+        assert_eq!(unavailable("ABC-1", "Closed", &[]), "ABC-1 can't be moved to `Closed`: no transitions available");
+        let unnamed = [json!({"id": "11"})];
+        assert_eq!(
+            unavailable("ABC-1", "Closed", &unnamed),
+            "ABC-1 can't be moved to `Closed`: no transitions available"
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use super::client::JiraClient;
+use anyhow::Result;
+use regex::Regex;
+use serde_json::Value;
+use slack_cmd_core::BotState;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+// channel setting to turn unfurling off, e.g. `config set jira.unfurl false`
+pub(super) const UNFURL_SETTING: &str = "jira.unfurl";
+// issues mentioned in one message beyond this are ignored
+pub(super) const MAX_UNFURLS: usize = 3;
+
+const ISSUES_NAMESPACE: &str = "jira:issues";
+const ISSUE_TTL: Duration = Duration::from_secs(300);
+
+pub(super) static ISSUE_KEY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b[A-Z][A-Z0-9]+-[0-9]+\b").unwrap());
+
+// issue fields cached in the storage, None if there is no such issue
+pub(super) async fn cached_issue(client: &JiraClient, bot_state: &BotState, key: &str) -> Result<Option<Value>> {
+    if let Some(issue) = bot_state.storage.get_json::<Value>(ISSUES_NAMESPACE, key).await? {
+        return Ok(Some(issue).filter(|issue| !issue.is_null()));
+    }
+    let path = format!("/rest/api/3/issue/{key}");
+    // things like UTF-8 look like issue keys too, so missing issues are cached as well
+    let issue = client.get_optional(&path, &[("fields", "summary,status,assignee,priority")]).await?;
+    let issue = issue.unwrap_or(Value::Null);
+    bot_state.storage.set_json(ISSUES_NAMESPACE, key, &issue, Some(ISSUE_TTL)).await?;
+    Ok(Some(issue).filter(|issue| !issue.is_null()))
+}
+
+pub(super) fn format_issue(client: &JiraClient, key: &str, issue: &Value) -> String {
+    let fields = &issue["fields"];
+    let summary = fields["summary"].as_str().unwrap_or_default();
+    let status = fields["status"]["name"].as_str().unwrap_or("-");
+    let assignee = fields["assignee"]["displayName"].as_str().unwrap_or("Unassigned");
+    let priority = fields["priority"]["name"].as_str().unwrap_or("-");
+    format!("<{}|{key}> {} | *{status}* | {assignee} | priority: {priority}", client.issue_url(key), escape(summary))
+}
+
+// slack mrkdwn control characters
+pub(super) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+// slack escapes &, < and > in message text
+pub(super) fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
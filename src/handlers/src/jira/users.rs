@@ -0,0 +1,70 @@
+use super::client::JiraClient;
+use anyhow::{anyhow, Result};
+use slack_cmd_core::{BotState, HandlerError};
+use slack_morphism::SlackUserId;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const ACCOUNTS_NAMESPACE: &str = "jira:accounts";
+const ACCOUNT_TTL: Duration = Duration::from_secs(24 * 3600);
+
+// Slack user -> jira accountId, matched by email.
+// Overrides are for users with different emails in Slack and Jira.
+pub(super) struct UserMapping {
+    // slack user id -> jira accountId
+    overrides: HashMap<String, String>,
+}
+
+impl UserMapping {
+    pub fn new(overrides: HashMap<String, String>) -> Self {
+        Self { overrides }
+    }
+
+    pub async fn account_id(&self, client: &JiraClient, bot_state: &BotState, user: &SlackUserId) -> Result<String> {
+        if let Some(account_id) = self.overrides.get(user.as_ref()) {
+            return Ok(account_id.clone());
+        }
+        if let Some(account_id) = bot_state.storage.get(ACCOUNTS_NAMESPACE, user.as_ref()).await? {
+            return Ok(account_id);
+        }
+        let account_id = find_account(client, bot_state, user).await?;
+        bot_state.storage.set(ACCOUNTS_NAMESPACE, user.as_ref(), &account_id, Some(ACCOUNT_TTL)).await?;
+        Ok(account_id)
+    }
+}
+
+async fn find_account(client: &JiraClient, bot_state: &BotState, user: &SlackUserId) -> Result<String> {
+    let slack_user = bot_state.slack_cli.get_user(user).await?;
+    let email = match slack_user.profile.and_then(|profile| profile.email) {
+        Some(email) => email.to_string(),
+        None => return Err(HandlerError::user(format!("Email of <@{user}> is not available")).into()),
+    };
+    let response = client.get("/rest/api/3/user/search", &[("query", &email)]).await?;
+    let accounts = response.as_array().cloned().unwrap_or_default();
+    let exact = accounts
+        .iter()
+        .find(|account| account["emailAddress"].as_str().is_some_and(|address| address.eq_ignore_ascii_case(&email)));
+    // jira hides emails depending on user privacy settings, and the query also matches names,
+    // so an account with hidden email is trusted only if it's the only one found
+    let hidden = accounts.iter().filter(|account| account["emailAddress"].as_str().is_none()).collect::<Vec<_>>();
+    let account = match (exact, hidden.as_slice()) {
+        (Some(account), _) => account,
+        (None, [account]) if accounts.len() == 1 => account,
+        (None, []) => return Err(HandlerError::user(format!("No jira account found for <@{user}>")).into()),
+        (None, _) => {
+            let msg = format!("Several jira accounts may belong to <@{user}>, map the user explicitly in `users`");
+            return Err(HandlerError::user(msg).into());
+        }
+    };
+    match account["accountId"].as_str() {
+        Some(account_id) => Ok(account_id.to_string()),
+        None => Err(anyhow!("accountId is missing in jira response")),
+    }
+}
+
+// user id of `<@U0123>` or `<@U0123|name>` mention
+pub(super) fn parse_mention(mention: &str) -> Option<SlackUserId> {
+    let user = mention.strip_prefix("<@")?.strip_suffix('>')?;
+    let user = user.split('|').next()?;
+    (!user.is_empty()).then(|| user.into())
+}
@@ -2,4 +2,12 @@ mod info;
 mod jira;
 
 pub use info::InfoHandler;
-pub use jira::JiraHandler;
+pub use jira::{JiraHandler, JiraHandlerOptions, JiraProjectDefaults};
+
+use slack_cmd_core::HandlerRegistry;
+
+// registers factories of all handlers, so they can be created from BotConfig
+pub fn register_handlers(registry: &mut HandlerRegistry) {
+    registry.register("info", InfoHandler::from_config);
+    registry.register("jira", JiraHandler::from_config);
+}